@@ -0,0 +1,998 @@
+use crate::{declares_variables, eval_literal};
+
+use std::sync::Arc;
+
+use type_lib::*;
+
+//turns the ast into bytecode for the vm, every function body, listener and overloading is compiled
+//into its own chunk once so nothing has to be cloned or walked again when it runs
+struct Compiler {
+    code: Vec<Op>,
+    //the number of scopes that are open at the current position of the chunk
+    scopes: usize,
+    //the loops around the current position, the innermost is last
+    loops: Vec<Loop>,
+    //the try blocks around the current position, the innermost is last
+    tries: Vec<TryBlock>,
+    //the code that is compiled, every chunk made from it points to it
+    source: Option<Arc<Source>>,
+}
+
+//break and continue are jumps, before jumping they close every scope and try block that was
+//opened inside of the loop
+struct Loop {
+    //the number of scopes and try blocks that were open when the loop started
+    scopes: usize,
+    tries: usize,
+    //jumps that still have to be patched with their targets
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+//try blocks are compiled inline, returning from one jumps to its end (closing the scopes opened
+//inside of it first)
+struct TryBlock {
+    //the number of scopes that were open when the try block started
+    scopes: usize,
+    //jumps to the end that still have to be patched
+    returns: Vec<usize>,
+}
+
+//compiles a whole program, when the last statement is an expression its value is returned (this is
+//what the repl and embedders get back)
+pub fn compile_program(
+    stmts: &[Statement],
+    source: Option<Arc<Source>>,
+) -> Result<Chunk, ThorLangError> {
+    let mut compiler = Compiler::new(source);
+
+    match stmts.split_last() {
+        Some((Statement::Do { expression, .. }, rest)) => {
+            compiler.statements(rest)?;
+            compiler.expression(expression)?;
+            compiler.emit(Op::Return);
+        }
+        _ => compiler.statements(stmts)?,
+    }
+
+    return Ok(compiler.finish());
+}
+
+//compiles a list of statements that is run in the environment it is given (function bodies,
+//listeners, overloadings...), falling off the end returns nil
+pub fn compile_block(stmts: &[Statement]) -> Result<Chunk, ThorLangError> {
+    let mut compiler = Compiler::new(None);
+
+    compiler.statements(stmts)?;
+
+    return Ok(compiler.finish());
+}
+
+//compiles a single expression that returns its value
+pub fn compile_expression(expr: &Expression) -> Result<Chunk, ThorLangError> {
+    let mut compiler = Compiler::new(None);
+
+    compiler.expression(expr)?;
+    compiler.emit(Op::Return);
+
+    return Ok(Chunk {
+        code: compiler.code,
+        source: None,
+    });
+}
+
+impl Compiler {
+    fn new(source: Option<Arc<Source>>) -> Self {
+        Compiler {
+            code: vec![],
+            scopes: 0,
+            loops: vec![],
+            tries: vec![],
+            source,
+        }
+    }
+
+    fn finish(mut self) -> Chunk {
+        self.emit(Op::Constant(Value::nil()));
+        self.emit(Op::Return);
+
+        Chunk {
+            code: self.code,
+            source: self.source,
+        }
+    }
+
+    //compiles a body (of a function, listener or overloading) into a chunk of its own that comes
+    //from the same source
+    fn block(&self, stmts: &[Statement]) -> Result<Chunk, ThorLangError> {
+        let mut compiler = Compiler::new(self.source.clone());
+
+        compiler.statements(stmts)?;
+
+        return Ok(compiler.finish());
+    }
+
+    //pushes the operation and returns its position (for patching jumps later)
+    fn emit(&mut self, op: Op) -> usize {
+        self.code.push(op);
+        self.code.len() - 1
+    }
+
+    fn here(&self) -> usize {
+        self.code.len()
+    }
+
+    //blocks only open a new scope when they declare variables (the resolver counts scopes the
+    //same way)
+    fn scoped(&mut self, stmts: &[Statement]) -> Result<(), ThorLangError> {
+        let declares = declares_variables(stmts);
+
+        if declares {
+            self.emit(Op::PushScope);
+            self.scopes += 1;
+        }
+        self.statements(stmts)?;
+        if declares {
+            self.emit(Op::PopScope);
+            self.scopes -= 1;
+        }
+
+        Ok(())
+    }
+
+    //compiles a block that leaves a value on the stack, the value of its last expression (or of the
+    //if it ends with, which is how else if chains are stored) or nil
+    fn value_block(&mut self, stmts: &[Statement]) -> Result<(), ThorLangError> {
+        let declares = declares_variables(stmts);
+
+        if declares {
+            self.emit(Op::PushScope);
+            self.scopes += 1;
+        }
+
+        match stmts.split_last() {
+            Some((Statement::Do { expression, .. }, rest)) => {
+                self.statements(rest)?;
+                self.expression(expression)?;
+            }
+            Some((
+                Statement::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                    ..
+                },
+                rest,
+            )) => {
+                self.statements(rest)?;
+                self.if_expression(
+                    condition,
+                    then_branch,
+                    else_branch.as_deref().map(|stmts| stmts.as_slice()),
+                )?;
+            }
+            _ => {
+                self.statements(stmts)?;
+                self.emit(Op::Constant(Value::nil()));
+            }
+        }
+
+        if declares {
+            self.emit(Op::PopScope);
+            self.scopes -= 1;
+        }
+
+        Ok(())
+    }
+
+    fn if_expression(
+        &mut self,
+        condition: &Expression,
+        then_branch: &[Statement],
+        else_branch: Option<&[Statement]>,
+    ) -> Result<(), ThorLangError> {
+        //break and continue would jump away from the middle of an expression and leave its
+        //operands on the stack, so inside of if expressions they are treated as outside of a loop
+        let loops = std::mem::take(&mut self.loops);
+
+        self.expression(condition)?;
+        let branch = self.emit(Op::Branch {
+            on_false: 0,
+            otherwise: 0,
+        });
+
+        self.value_block(then_branch)?;
+        let then_jump = self.emit(Op::Jump(0));
+
+        let on_false = self.here();
+        self.value_block(else_branch.unwrap_or_default())?;
+        let else_jump = self.emit(Op::Jump(0));
+
+        //conditions that are not bools skip both branches (like in if statements)
+        let otherwise = self.emit(Op::Constant(Value::nil()));
+
+        let end = self.here();
+
+        self.code[branch] = Op::Branch {
+            on_false,
+            otherwise,
+        };
+        self.code[then_jump] = Op::Jump(end);
+        self.code[else_jump] = Op::Jump(end);
+
+        self.loops = loops;
+
+        Ok(())
+    }
+
+    fn enter_loop(&mut self) {
+        self.loops.push(Loop {
+            scopes: self.scopes,
+            tries: self.tries.len(),
+            breaks: vec![],
+            continues: vec![],
+        });
+    }
+
+    //points the breaks and continues of the innermost loop to their targets
+    fn exit_loop(&mut self, continue_target: usize, break_target: usize) {
+        let finished = self.loops.pop().unwrap();
+
+        for jump in finished.continues {
+            self.code[jump] = Op::Jump(continue_target);
+        }
+        for jump in finished.breaks {
+            self.code[jump] = Op::Jump(break_target);
+        }
+    }
+
+    //closes the scopes opened inside of the innermost loop and emits a jump that is patched when
+    //the loop is finished
+    fn loop_jump(&mut self, keyword_token_index: usize) -> Result<usize, ThorLangError> {
+        let Some(current) = self.loops.last() else {
+            return Err(ThorLangError::NotInLoop {
+                keyword_token_index,
+            });
+        };
+
+        let (scopes, tries) = (current.scopes, current.tries);
+
+        for _ in scopes..self.scopes {
+            self.emit(Op::PopScope);
+        }
+        for _ in tries..self.tries.len() {
+            self.emit(Op::LeaveTry);
+        }
+
+        Ok(self.emit(Op::Jump(0)))
+    }
+
+    //the try block runs inline with a handler that turns errors into its value. break and continue
+    //can only leave it when it is not in the middle of an expression (like in if expressions)
+    fn try_block(&mut self, block: &[Statement], in_statement: bool) -> Result<(), ThorLangError> {
+        let loops = if in_statement {
+            None
+        } else {
+            Some(std::mem::take(&mut self.loops))
+        };
+
+        let start = self.emit(Op::TryStart(0));
+
+        self.tries.push(TryBlock {
+            scopes: self.scopes,
+            returns: vec![],
+        });
+
+        self.scoped(block)?;
+        self.emit(Op::Constant(Value::nil()));
+
+        let finished = self.tries.pop().unwrap();
+        let end = self.emit(Op::TryEnd);
+
+        for jump in finished.returns {
+            self.code[jump] = Op::Jump(end);
+        }
+
+        //errors continue after the end with the error as the value of the block
+        self.code[start] = Op::TryStart(self.here());
+
+        if let Some(loops) = loops {
+            self.loops = loops;
+        }
+
+        Ok(())
+    }
+
+    //the value of a statement, try blocks there can be left with break and continue
+    fn statement_value(&mut self, expression: &Expression) -> Result<(), ThorLangError> {
+        if let Expression::Try { block } = expression {
+            return self.try_block(block, true);
+        }
+
+        self.expression(expression)
+    }
+
+    fn statements(&mut self, stmts: &[Statement]) -> Result<(), ThorLangError> {
+        for stmt in stmts {
+            self.statement(stmt)?;
+        }
+
+        Ok(())
+    }
+
+    fn statement(&mut self, stmt: &Statement) -> Result<(), ThorLangError> {
+        match stmt {
+            Statement::Throw {
+                exception,
+                throw_token_index,
+            } => {
+                self.expression(exception)?;
+                self.emit(Op::Throw(*throw_token_index));
+            }
+            Statement::Overload {
+                operator,
+                operands,
+                operation,
+                line: _,
+            } => {
+                self.emit(Op::Overload {
+                    operator: operator.clone(),
+                    operands: operands.to_vec(),
+                    operation: Arc::new(self.block(operation)?),
+                });
+            }
+            //inside of a try block return only leaves the block
+            Statement::Return {
+                expression,
+                line: _,
+            } => {
+                self.expression(expression)?;
+
+                match self.tries.last() {
+                    Some(current) => {
+                        for _ in current.scopes..self.scopes {
+                            self.emit(Op::PopScope);
+                        }
+
+                        let jump = self.emit(Op::Jump(0));
+                        self.tries.last_mut().unwrap().returns.push(jump);
+                    }
+                    None => {
+                        self.emit(Op::Return);
+                    }
+                }
+            }
+            Statement::Function {
+                name,
+                body,
+                arguments,
+                arity,
+                line: _,
+                slot,
+            } => {
+                self.emit(Op::Function {
+                    function: Arc::new(FunctionProto {
+                        name: name.to_string(),
+                        arguments: arguments.to_vec(),
+                        arity: *arity,
+                        body: Arc::new(self.block(body)?),
+                    }),
+                    slot: *slot,
+                });
+            }
+            //a block just opens a new env tree branch
+            Statement::Block {
+                statements,
+                line: _,
+            } => self.scoped(statements)?,
+            //a condition that is no bool runs neither branch
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                line: _,
+            } => {
+                self.expression(condition)?;
+                let branch = self.emit(Op::Branch {
+                    on_false: 0,
+                    otherwise: 0,
+                });
+
+                self.scoped(then_branch)?;
+
+                let on_false;
+
+                if let Some(else_branch) = else_branch {
+                    let jump = self.emit(Op::Jump(0));
+
+                    on_false = self.here();
+                    self.scoped(else_branch)?;
+
+                    self.code[jump] = Op::Jump(self.here());
+                } else {
+                    on_false = self.here();
+                }
+
+                self.code[branch] = Op::Branch {
+                    on_false,
+                    otherwise: self.here(),
+                };
+            }
+            Statement::While {
+                condition,
+                block,
+                line: _,
+            } => {
+                let start = self.here();
+
+                self.expression(condition)?;
+                let branch = self.emit(Op::Branch {
+                    on_false: 0,
+                    otherwise: 0,
+                });
+
+                self.enter_loop();
+                self.scoped(block)?;
+                self.emit(Op::Jump(start));
+
+                let exit = self.here();
+                self.code[branch] = Op::Branch {
+                    on_false: exit,
+                    otherwise: exit,
+                };
+
+                self.exit_loop(start, exit);
+            }
+            //the array and the index of the current element stay on the stack while the loop runs,
+            //every iteration gets its own scope
+            Statement::For {
+                iterator,
+                iteration_variable,
+                block,
+            } => {
+                self.expression(iterator)?;
+                self.emit(Op::Constant(Value::number(0.0)));
+
+                let variable = iteration_variable.get_content().unwrap_or_default();
+
+                let start = self.emit(Op::Iterate {
+                    variable: variable.clone(),
+                    exit: 0,
+                });
+
+                self.scopes += 1;
+                self.enter_loop();
+                self.statements(block)?;
+
+                let next = self.emit(Op::PopScope);
+                self.emit(Op::Jump(start));
+
+                //breaking has to clean up the scope of the iteration and the stack as well
+                let stop = self.emit(Op::PopScope);
+                self.emit(Op::Pop);
+                self.emit(Op::Pop);
+
+                self.exit_loop(next, stop);
+                self.scopes -= 1;
+
+                self.code[start] = Op::Iterate {
+                    variable,
+                    exit: self.here(),
+                };
+            }
+            //the default is only evaluated when the argument was left out
+            Statement::DefaultArgument {
+                name,
+                index,
+                default,
+            } => {
+                let skip = self.emit(Op::SkipDefault {
+                    index: *index,
+                    skip: 0,
+                });
+
+                self.expression(default)?;
+                self.emit(Op::Define {
+                    name: name.to_string(),
+                    slot: Some(*index),
+                });
+
+                self.code[skip] = Op::SkipDefault {
+                    index: *index,
+                    skip: self.here(),
+                };
+            }
+            Statement::Break { break_token_index } => {
+                let jump = self.loop_jump(*break_token_index)?;
+                self.loops.last_mut().unwrap().breaks.push(jump);
+            }
+            Statement::Continue {
+                continue_token_index,
+            } => {
+                let jump = self.loop_jump(*continue_token_index)?;
+                self.loops.last_mut().unwrap().continues.push(jump);
+            }
+            Statement::Print {
+                expression,
+                line: _,
+            } => {
+                self.expression(expression)?;
+                self.emit(Op::Print);
+            }
+            Statement::Do {
+                expression,
+                line: _,
+            } => {
+                self.statement_value(expression)?;
+                self.emit(Op::Pop);
+            }
+            Statement::Variable {
+                name,
+                expression,
+                slot,
+                ..
+            } => {
+                self.statement_value(expression)?;
+                self.emit(Op::Define {
+                    name: name.to_string(),
+                    slot: *slot,
+                });
+            }
+            Statement::Destructure {
+                pattern,
+                expression,
+                token_index,
+                slots,
+                ..
+            } => {
+                self.expression(expression)?;
+                self.destructure(pattern, &mut slots.iter().copied(), *token_index)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    //takes the value on top of the stack apart and declares the names of the pattern, the slots are
+    //handed out in the order of Pattern::bindings
+    fn destructure(
+        &mut self,
+        pattern: &Pattern,
+        slots: &mut impl Iterator<Item = Option<usize>>,
+        token_index: usize,
+    ) -> Result<(), ThorLangError> {
+        match pattern {
+            Pattern::Binding { name, .. } => {
+                self.emit(Op::Define {
+                    name: name.to_string(),
+                    slot: slots.next().flatten(),
+                });
+            }
+            Pattern::Array { elements, rest } => {
+                for (index, element) in elements.iter().enumerate() {
+                    self.destructure_element(
+                        DestructureKey::Index(index),
+                        element,
+                        slots,
+                        token_index,
+                    )?;
+                }
+
+                if let Some(rest) = rest {
+                    self.destructure_element(
+                        DestructureKey::Rest(elements.len()),
+                        rest,
+                        slots,
+                        token_index,
+                    )?;
+                }
+
+                self.emit(Op::Pop);
+            }
+            Pattern::Object { keys, values } => {
+                for (key, value) in keys.iter().zip(values) {
+                    self.destructure_element(
+                        DestructureKey::Field(key.to_string()),
+                        value,
+                        slots,
+                        token_index,
+                    )?;
+                }
+
+                self.emit(Op::Pop);
+            }
+            //the parser only allows names, arrays and objects (and _) in destructuring patterns
+            _ => {
+                self.emit(Op::Pop);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn destructure_element(
+        &mut self,
+        key: DestructureKey,
+        pattern: &Pattern,
+        slots: &mut impl Iterator<Item = Option<usize>>,
+        token_index: usize,
+    ) -> Result<(), ThorLangError> {
+        let (pattern, default) = match pattern {
+            Pattern::Default { pattern, default } => (pattern.as_ref(), Some(default)),
+            _ => (pattern, None),
+        };
+
+        let destructure = self.emit(Op::Destructure {
+            key: key.clone(),
+            default: None,
+            token_index,
+        });
+
+        //the default is only evaluated when the element is missing
+        if let Some(default) = default {
+            let skip = self.emit(Op::Jump(0));
+
+            let default_start = self.here();
+            self.expression(default)?;
+
+            self.code[destructure] = Op::Destructure {
+                key,
+                default: Some(default_start),
+                token_index,
+            };
+            self.code[skip] = Op::Jump(self.here());
+        }
+
+        self.destructure(pattern, slots, token_index)
+    }
+
+    //operands are pushed in the order they are evaluated in (the key of a retrieve comes before the
+    //retrievee, the value of an assignment before the keys of its target)
+    fn expression(&mut self, expr: &Expression) -> Result<(), ThorLangError> {
+        match expr {
+            Expression::Retrieve {
+                retrievee,
+                key,
+                lbrack_token_index,
+            } => {
+                self.expression(key)?;
+                self.expression(retrievee)?;
+                self.emit(Op::Retrieve(*lbrack_token_index));
+            }
+            Expression::Lambda {
+                block,
+                arguments,
+                arity,
+            } => {
+                self.emit(Op::Lambda(Arc::new(FunctionProto {
+                    name: "#lambda#".to_string(),
+                    arguments: arguments.to_vec(),
+                    arity: *arity,
+                    body: Arc::new(self.block(block)?),
+                })));
+            }
+            Expression::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => self.if_expression(condition, then_branch, else_branch.as_deref())?,
+            //the value stays on the stack while the arms are tried, every arm that matches opens
+            //the scope with its bindings and closes it again after its guard failed or its body ran
+            Expression::Match {
+                value,
+                arms,
+                match_token_index,
+            } => {
+                self.expression(value)?;
+
+                let mut ends = vec![];
+
+                for arm in arms {
+                    let names = arm
+                        .pattern
+                        .bindings()
+                        .into_iter()
+                        .map(|(name, _)| name)
+                        .collect();
+
+                    let test = self.emit(Op::Match {
+                        pattern: arm.pattern.clone(),
+                        names,
+                        next: 0,
+                    });
+                    self.scopes += 1;
+
+                    let mut guard_branch = None;
+
+                    if let Some(guard) = &arm.guard {
+                        self.expression(guard)?;
+                        guard_branch = Some(self.emit(Op::Branch {
+                            on_false: 0,
+                            otherwise: 0,
+                        }));
+                    }
+
+                    self.expression(&arm.body)?;
+                    self.emit(Op::PopScope);
+                    ends.push(self.emit(Op::Jump(0)));
+
+                    //guards that are not true (like conditions that are not bools) go on with
+                    //the next arm
+                    if let Some(guard_branch) = guard_branch {
+                        let failed = self.emit(Op::PopScope);
+
+                        self.code[guard_branch] = Op::Branch {
+                            on_false: failed,
+                            otherwise: failed,
+                        };
+                    }
+
+                    self.scopes -= 1;
+
+                    let here = self.here();
+
+                    if let Op::Match { next, .. } = &mut self.code[test] {
+                        *next = here;
+                    }
+                }
+
+                self.emit(Op::NoMatch(*match_token_index));
+
+                let end = self.here();
+
+                for jump in ends {
+                    self.code[jump] = Op::Jump(end);
+                }
+
+                //the value of the arm replaces the matched value
+                self.emit(Op::Swap);
+                self.emit(Op::Pop);
+            }
+            Expression::Try { block } => self.try_block(block, false)?,
+            Expression::On {
+                block,
+                variables,
+                on_token_index,
+            } => {
+                if let Some(block) = block {
+                    let listener = Arc::new(self.block(block)?);
+
+                    for variable in variables {
+                        let (name, slot) = match variable {
+                            Expression::Identifier { name, slot, .. } => {
+                                (Some(name.to_string()), *slot)
+                            }
+                            _ => (None, None),
+                        };
+
+                        self.expression(variable)?;
+                        self.emit(Op::Listen {
+                            name,
+                            slot,
+                            listener: listener.clone(),
+                            on_token_index: *on_token_index,
+                        });
+                    }
+
+                    self.emit(Op::Constant(Value::nil()));
+                } else {
+                    self.emit(Op::EvalError(*on_token_index));
+                }
+            }
+            Expression::FieldCall {
+                callee,
+                key,
+                dot_token_index,
+            } => {
+                self.expression(callee)?;
+
+                //identifiers are field names, everything else is evaluated and hashed
+                if let Expression::Identifier { name, .. } = key.as_ref() {
+                    self.emit(Op::Field {
+                        key: Some(name.to_string()),
+                        dot_token_index: *dot_token_index,
+                    });
+                } else {
+                    self.expression(key)?;
+                    self.emit(Op::Field {
+                        key: None,
+                        dot_token_index: *dot_token_index,
+                    });
+                }
+            }
+            Expression::Array { values } => {
+                for value in values {
+                    self.expression(value)?;
+                }
+                self.emit(Op::Array(values.len()));
+            }
+            Expression::Object { keys, values } => {
+                for (key, value) in keys.iter().zip(values) {
+                    self.expression(key)?;
+                    self.expression(value)?;
+                }
+                self.emit(Op::Object(keys.len()));
+            }
+            Expression::Interpolation {
+                parts,
+                string_token_index,
+            } => {
+                for part in parts {
+                    self.expression(part)?;
+                }
+                self.emit(Op::Interpolate {
+                    parts: parts.len(),
+                    string_token_index: *string_token_index,
+                });
+            }
+            Expression::Call {
+                callee,
+                paren_token_index,
+                arguments,
+                names,
+            } => {
+                self.expression(callee)?;
+
+                for argument in arguments {
+                    self.expression(argument)?;
+                }
+
+                self.emit(Op::Call {
+                    arguments: arguments.len(),
+                    names: names.to_vec(),
+                    paren_token_index: *paren_token_index,
+                });
+            }
+            Expression::Assignment {
+                target,
+                value,
+                eq_token_index,
+                operator,
+            } => {
+                self.expression(value)?;
+                self.assignment(target, *eq_token_index, operator.clone())?;
+            }
+            Expression::Identifier {
+                name,
+                identifier_token_index,
+                slot,
+            } => {
+                self.emit(Op::Get {
+                    name: name.to_string(),
+                    slot: *slot,
+                    identifier_token_index: *identifier_token_index,
+                });
+            }
+            Expression::Unary {
+                operator,
+                right,
+                operator_token_index,
+            } => {
+                self.expression(right)?;
+                self.emit(Op::Unary {
+                    operator: operator.clone(),
+                    operator_token_index: *operator_token_index,
+                });
+            }
+            //literals are turned into values right away
+            Expression::Literal {
+                literal,
+                literal_token_index,
+            } => match eval_literal(literal.clone(), *literal_token_index) {
+                Ok(value) => {
+                    self.emit(Op::Constant(value));
+                }
+                Err(_) => {
+                    self.emit(Op::EvalError(*literal_token_index));
+                }
+            },
+            Expression::Grouping { inner } => self.expression(inner)?,
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                operator_token_index,
+            } => {
+                self.expression(left)?;
+                self.expression(right)?;
+                self.emit(Op::Binary {
+                    operator: operator.clone(),
+                    operator_token_index: *operator_token_index,
+                });
+            }
+            //the left side jumps over the right one when it decides the result, the right side
+            //jumps over the constant when it decides it, else the result is the constant (true
+            //for and, false for or)
+            Expression::Logical {
+                left,
+                operator,
+                right,
+                operator_token_index,
+            } => {
+                let logical = Op::Logical {
+                    operator: operator.clone(),
+                    end: 0,
+                    operator_token_index: *operator_token_index,
+                };
+
+                self.expression(left)?;
+                let short_circuit = self.emit(logical.clone());
+
+                self.expression(right)?;
+                let check = self.emit(logical);
+
+                self.emit(Op::Constant(Value::bool(*operator == TokenType::AND)));
+
+                let end = self.here();
+
+                for jump in [short_circuit, check] {
+                    if let Op::Logical { end: target, .. } = &mut self.code[jump] {
+                        *target = end;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    //walks the target from the outside in (obj.hello[0] visits [0] first), computed keys are
+    //pushed in that order and the steps are stored from the variable outwards
+    fn assignment(
+        &mut self,
+        target: &Expression,
+        eq_token_index: usize,
+        operator: Option<TokenType>,
+    ) -> Result<(), ThorLangError> {
+        let mut steps = vec![];
+        let mut current = target;
+
+        let (name, slot) = loop {
+            match current {
+                Expression::Identifier { name, slot, .. } => break (name.to_string(), *slot),
+                Expression::FieldCall {
+                    callee,
+                    key,
+                    dot_token_index,
+                } => {
+                    if let Expression::Identifier { name, .. } = key.as_ref() {
+                        steps.push(AssignStep::Field(name.to_string(), *dot_token_index));
+                    } else {
+                        self.expression(key)?;
+                        steps.push(AssignStep::Hashed(*dot_token_index));
+                    }
+
+                    current = callee;
+                }
+                Expression::Retrieve {
+                    retrievee,
+                    key,
+                    lbrack_token_index,
+                } => {
+                    self.expression(key)?;
+                    steps.push(AssignStep::Key(*lbrack_token_index));
+
+                    current = retrievee;
+                }
+                //only variables and their fields can be assigned to
+                _ => {
+                    return Err(ThorLangError::EvalError {
+                        operation_token_index: eq_token_index,
+                    })
+                }
+            }
+        };
+
+        steps.reverse();
+
+        self.emit(Op::Assign {
+            name,
+            slot,
+            steps,
+            eq_token_index,
+            operator,
+        });
+
+        Ok(())
+    }
+}
@@ -1,140 +1,653 @@
-use type_lib::{stringify_value, ThorLangError, Token, TokenType, ValueType};
+use type_lib::{stringify_value, Source, ThorLangError, Token, TokenType, TraceFrame, ValueType};
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+//ansi escape codes used when rendering with color
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+//a part of the code, offset and length are in bytes, line and column (both start at 1, the column
+//is counted in characters) tell where it starts and end_line and end_column where it ends (the end
+//column is the one right after the span)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub offset: usize,
+    pub length: usize,
+    pub line: i32,
+    pub column: i32,
+    pub end_line: i32,
+    pub end_column: i32,
+}
+
+impl Span {
+    //the span of the given bytes of the text, lines and columns are counted from the text itself
+    //so they are also right for the eof token (which has no column)
+    pub fn new(text: &str, offset: usize, length: usize) -> Span {
+        let offset = offset.min(text.len());
+        let length = length.min(text.len() - offset);
+
+        let (line, column) = line_column(text, offset);
+        let (end_line, end_column) = line_column(text, offset + length);
+
+        return Span {
+            offset,
+            length,
+            line,
+            column,
+            end_line,
+            end_column,
+        };
+    }
+
+    //lexer errors only know the line and column they happened on
+    fn at(text: &str, line: i32, column: i32, length: usize) -> Span {
+        let mut offset = 0;
+
+        for (index, code) in text.split('\n').enumerate() {
+            if index as i32 + 1 == line {
+                offset += code
+                    .char_indices()
+                    .nth((column - 1).max(0) as usize)
+                    .map(|(index, _)| index)
+                    .unwrap_or(code.len());
+                break;
+            }
+
+            offset += code.len() + 1;
+        }
+
+        return Span::new(text, offset, length);
+    }
+}
+
+//the line and column of a byte offset into the text
+fn line_column(text: &str, offset: usize) -> (i32, i32) {
+    let before = text.get(..offset).unwrap_or_default();
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+
+    return (
+        before.matches('\n').count() as i32 + 1,
+        before[line_start..].chars().count() as i32 + 1,
+    );
+}
+
+//a call an error passed through, span is the name of the called function at the call site (calls
+//made by natives have none)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallSite {
+    pub function: String,
+    pub file: Option<String>,
+    pub span: Option<Span>,
+}
+
+//a span with a short message that is printed below its underline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+//an error together with everything needed to show it to the user, this is what embedders get back
+//instead of the printed message. rendered it looks like this:
+//
+//error[E0011]: the value `x` is not found in the current scope
+// --> 1:7
+//  |
+//1 | print x;
+//  |       ^ not found
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub error: ThorLangError,
+    pub code: &'static str,
+    pub message: String,
+    //where the error happened, errors without a place in the code (like runtime errors) have none
+    pub primary: Option<Label>,
+    //other places in the code that help to understand the error
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    //the lines of code the labels point into, by their line number
+    pub source_lines: BTreeMap<i32, String>,
+    //the file the error happened in, none when the code doesn't come from a file
+    pub file: Option<String>,
+    //the calls the error passed through, from the innermost to the outermost
+    pub trace: Vec<CallSite>,
+}
+
+impl Diagnostic {
+    //a diagnostic that only has a message, the code is taken from the error
+    pub fn new(error: ThorLangError, message: String) -> Diagnostic {
+        return Diagnostic {
+            code: error.code(),
+            error,
+            message,
+            primary: None,
+            labels: vec![],
+            notes: vec![],
+            source_lines: BTreeMap::new(),
+            file: None,
+            trace: vec![],
+        };
+    }
+
+    //renders the diagnostic the way rustc does, color uses ansi escape codes so it should only be
+    //turned on when printing to a terminal
+    pub fn render(&self, color: bool) -> String {
+        let paint = |style: &str, text: &str| -> String {
+            if color {
+                return format!("{style}{text}{RESET}");
+            }
+
+            return text.to_string();
+        };
+
+        let mut out = format!(
+            "{}{}",
+            paint(RED, &format!("error[{}]", self.code)),
+            paint(BOLD, &format!(": {}", self.message))
+        );
+
+        //underlines are shown from the top left to the bottom right of the code
+        let mut labels: Vec<(&Label, bool)> = self
+            .primary
+            .iter()
+            .map(|label| (label, true))
+            .chain(self.labels.iter().map(|label| (label, false)))
+            .collect();
+
+        labels.sort_by_key(|(label, _)| (label.span.line, label.span.column));
+
+        let width = labels
+            .iter()
+            .map(|(label, _)| label.span.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+
+        let gutter = " ".repeat(width);
+        let bar = paint(BLUE, "|");
+
+        if let Some(primary) = &self.primary {
+            out += &format!(
+                "\n{gutter}{} {}",
+                paint(BLUE, "-->"),
+                location(self.file.as_deref(), &primary.span)
+            );
+            out += &format!("\n{gutter} {bar}");
+        }
+
+        let mut previous_line: Option<i32> = None;
+
+        for (label, is_primary) in labels {
+            let Some(code) = self.source_lines.get(&label.span.line) else {
+                continue;
+            };
+
+            //every line is shown once, with the underlines of all of its labels below it
+            if previous_line != Some(label.span.line) {
+                if previous_line.is_some_and(|line| label.span.line > line + 1) {
+                    out += &format!("\n{}", paint(BLUE, "..."));
+                }
+
+                out += &format!(
+                    "\n{} {bar} {code}",
+                    paint(BLUE, &format!("{:>width$}", label.span.line))
+                );
+
+                previous_line = Some(label.span.line);
+            }
+
+            let (indent, length) = underline(code, &label.span);
+            let (marker, style) = if is_primary { ("^", RED) } else { ("-", BLUE) };
+            let underline = format!("{} {}", marker.repeat(length), label.message);
+
+            out += &format!("\n{gutter} {bar} {indent}{}", paint(style, underline.trim_end()));
+        }
+
+        if self.primary.is_some() && (!self.notes.is_empty() || !self.trace.is_empty()) {
+            out += &format!("\n{gutter} {bar}");
+        }
+
+        for note in &self.notes {
+            out += &format!("\n{gutter} = {}: {note}", paint(BOLD, "note"));
+        }
+
+        for call in &self.trace {
+            let site = match &call.span {
+                Some(span) => format!("called at {}", location(call.file.as_deref(), span)),
+                None => "called by a native function".to_string(),
+            };
+
+            out += &format!(
+                "\n{gutter} = {}: in `{}`, {site}",
+                paint(BOLD, "trace"),
+                call.function
+            );
+        }
+
+        return out;
+    }
+
+    //the diagnostic as one line of json for editors and other tools, file is the path the code was
+    //read from (the file of the diagnostic wins, errors in modules have the one of the module). the
+    //position of the diagnostic is the one of its primary label (null without one), spans holds all
+    //labels and trace the calls with their call site (null for calls made by natives)
+    pub fn to_json(&self, file: &str) -> String {
+        let file = self.file.as_deref().unwrap_or(file);
+
+        let span = |label: &Label, primary: bool| {
+            format!(
+                "{{\"file\":{},\"line\":{},\"column\":{},\"end_line\":{},\"end_column\":{},\"byte_start\":{},\"byte_end\":{},\"primary\":{},\"label\":{}}}",
+                json_string(file),
+                label.span.line,
+                label.span.column,
+                label.span.end_line,
+                label.span.end_column,
+                label.span.offset,
+                label.span.offset + label.span.length,
+                primary,
+                json_string(&label.message)
+            )
+        };
+
+        let spans: Vec<String> = self
+            .primary
+            .iter()
+            .map(|label| span(label, true))
+            .chain(self.labels.iter().map(|label| span(label, false)))
+            .collect();
+
+        let notes: Vec<String> = self.notes.iter().map(|note| json_string(note)).collect();
+
+        let position = match &self.primary {
+            Some(primary) => format!(
+                "\"line\":{},\"column\":{},\"end_line\":{},\"end_column\":{}",
+                primary.span.line,
+                primary.span.column,
+                primary.span.end_line,
+                primary.span.end_column
+            ),
+            None => "\"line\":null,\"column\":null,\"end_line\":null,\"end_column\":null".to_string(),
+        };
+
+        let trace: Vec<String> = self
+            .trace
+            .iter()
+            .map(|call| {
+                let (line, column) = match &call.span {
+                    Some(span) => (span.line.to_string(), span.column.to_string()),
+                    None => ("null".to_string(), "null".to_string()),
+                };
+
+                format!(
+                    "{{\"function\":{},\"file\":{},\"line\":{line},\"column\":{column}}}",
+                    json_string(&call.function),
+                    json_string(call.file.as_deref().unwrap_or(file))
+                )
+            })
+            .collect();
+
+        return format!(
+            "{{\"file\":{},\"severity\":\"error\",\"code\":{},\"message\":{},{},\"spans\":[{}],\"notes\":[{}],\"trace\":[{}],\"rendered\":{}}}",
+            json_string(file),
+            json_string(self.code),
+            json_string(&self.message),
+            position,
+            spans.join(","),
+            notes.join(","),
+            trace.join(","),
+            json_string(&self.render(false))
+        );
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+//file:line:column of a span, just line:column for code that isn't from a file
+fn location(file: Option<&str>, span: &Span) -> String {
+    return match file {
+        Some(file) => format!("{file}:{}:{}", span.line, span.column),
+        None => format!("{}:{}", span.line, span.column),
+    };
+}
+
+//the call site of a call in the trace, the name of the function in front of the ( is underlined.
+//calls without their own source were made in the code of the diagnostic
+pub fn call_site(frame: &TraceFrame, source: Option<&Source>) -> CallSite {
+    let source = frame.source.as_deref().or(source);
+
+    let span = frame
+        .call_token_index
+        .and_then(|index| index.checked_sub(1))
+        .zip(source)
+        .and_then(|(index, source)| {
+            let token = source.tokens.get(index)?;
+
+            Some(Span::new(&source.text, token.offset, token.length))
+        });
+
+    return CallSite {
+        function: frame.function.clone(),
+        file: source.and_then(|source| source.name.clone()),
+        span,
+    };
+}
+
+//a json string literal of the text
+fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+
+    for char in text.chars() {
+        match char {
+            '"' => json += "\\\"",
+            '\\' => json += "\\\\",
+            '\n' => json += "\\n",
+            '\r' => json += "\\r",
+            '\t' => json += "\\t",
+            char if (char as u32) < 0x20 => json += &format!("\\u{:04x}", char as u32),
+            char => json.push(char),
+        }
+    }
+
+    json.push('"');
+
+    return json;
+}
+
+//the whitespace in front of the underline of a span and how many characters it underlines, spans
+//that go on over the next lines are only underlined up to the end of their first line
+fn underline(code: &str, span: &Span) -> (String, usize) {
+    let column = (span.column - 1).max(0) as usize;
+
+    //tabs are kept so the underline lines up with the code above it
+    let indent = code
+        .chars()
+        .take(column)
+        .map(|char| if char == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    let start = code
+        .char_indices()
+        .nth(column)
+        .map(|(index, _)| index)
+        .unwrap_or(code.len());
+    let end = (start + span.length).min(code.len());
+
+    let length = code
+        .get(start..end)
+        .map(|part| part.chars().count())
+        .unwrap_or(1);
+
+    return (indent, length.max(1));
+}
 
 //handles error when parsing (unexpected tokens and typos)
 pub fn handle_error(text: String, tokens: Vec<Token>, error: ThorLangError) {
-    let text_lines: Vec<&str> = text.split("\n").collect();
+    let diagnostic = diagnose(&text, &tokens, error);
 
+    println!("\n{diagnostic}\n");
+}
+
+//turns an error into a diagnostic, the tokens have to be the ones the failing code was lexed into
+pub fn diagnose(text: &str, tokens: &[Token], error: ThorLangError) -> Diagnostic {
     //whenever possible thorlang will try to aid you to improve your code with a message and
     //pointing where the error occured
 
-    let mut msg: String = Default::default();
-    let mut error_line: String = Default::default();
-    let mut pointer: &str = "";
-    let mut tip: String = Default::default();
+    //tokens are only looked up with get, a wrong index leaves out the label instead of panicking
+    let token = |index: Option<usize>| index.and_then(|index| tokens.get(index));
+
+    //the code a token was lexed from, quoted for messages (? when the token is missing)
+    let quoted = |token: Option<&Token>| {
+        let code = token.and_then(|token| text.get(token.offset..token.offset + token.length));
+
+        format!("`{}`", code.unwrap_or("?"))
+    };
+
+    let label = |token: &Token, message: &str| Label {
+        span: Span::new(text, token.offset, token.length),
+        message: message.to_string(),
+    };
+
+    //traced errors know the code they happened in, their calls become the trace
+    if let ThorLangError::Traced {
+        error: inner,
+        source,
+        trace,
+    } = &error
+    {
+        let mut diagnostic = match source {
+            Some(source) => diagnose(&source.text, &source.tokens, *inner.clone()),
+            None => diagnose(text, tokens, *inner.clone()),
+        };
+
+        diagnostic.file = source.as_ref().and_then(|source| source.name.clone());
+        diagnostic.trace = trace
+            .iter()
+            .map(|frame| call_site(frame, source.as_deref()))
+            .collect();
+        diagnostic.error = error;
+
+        return diagnostic;
+    }
+
+    let mut diagnostic = Diagnostic::new(error.clone(), String::new());
 
     //almost all of the below work in the same way, the token_indices are retrieved from the token
-    //list and then their value is displayed in the error message, then the entire line of the
-    //error is displayed
+    //list, their code is used in the message and their spans are underlined
     match error {
+        //lexer errors already know where they happened
+        ThorLangError::InvalidEscape {
+            escape,
+            line,
+            column,
+        } => {
+            diagnostic.message = format!("invalid escape sequence `{}` in string", escape);
+            diagnostic.primary = Some(Label {
+                span: Span::at(text, line, column, escape.len()),
+                message: "unknown escape".to_string(),
+            });
+            diagnostic.notes.push(
+                "valid escapes are \\n, \\t, \\\\, \\\", \\$ and \\u{...}".to_string(),
+            );
+        }
+        ThorLangError::UnterminatedString { line, column } => {
+            diagnostic.message = "unterminated string".to_string();
+            diagnostic.primary = Some(Label {
+                span: Span::at(text, line, column, 1),
+                message: "the string starts here and is never closed".to_string(),
+            });
+        }
+        ThorLangError::UnexpectedToken {
+            expected,
+            encountered,
+        } => {
+            let found = token(Some(encountered));
+            let previous = token(encountered.checked_sub(1));
+
+            let expected_tokens = expected
+                .iter()
+                .map(|token_type| stringify_token_type(token_type.clone()))
+                .collect::<Vec<&str>>()
+                .join(", ");
+
+            let expected_tokens = if expected.len() > 1 {
+                format!("one of {}", expected_tokens)
+            } else {
+                expected_tokens
+            };
+
+            let found_token = match found {
+                Some(token) if token.token_type == TokenType::EOF => {
+                    "the end of the code".to_string()
+                }
+                Some(_) => quoted(found),
+                None => "nothing".to_string(),
+            };
+
+            diagnostic.message = format!("expected {}, found {}", expected_tokens, found_token);
+            diagnostic.primary = found.map(|token| label(token, &format!("expected {}", expected_tokens)));
+            diagnostic.labels.extend(previous.map(|token| label(token, "after this")));
+        }
         ThorLangError::UnknownFunctionError {
             function_paren_token,
         } => {
-            let function_paren_token = tokens[function_paren_token - 1].clone();
+            let callee = token(function_paren_token.checked_sub(1));
 
-            msg = format!(
-                "the function '{}' on line {}:{}, is not found in the current scope",
-                function_paren_token.token_type.get_content().unwrap(),
-                function_paren_token.line,
-                function_paren_token.column
+            diagnostic.message = format!(
+                "the function {} is not found in the current scope",
+                quoted(callee)
             );
-
-            error_line = format!(
-                "{} | {}",
-                function_paren_token.line,
-                text_lines[function_paren_token.line as usize - 1]
-            )
+            diagnostic.primary = callee.map(|token| label(token, "not found"));
+            diagnostic
+                .notes
+                .push("values that are not functions can't be called either".to_string());
         }
         ThorLangError::UnknownValueError {
             identifier_token_index,
         } => {
-            let unknown_value_token = tokens[identifier_token_index - 1].clone();
+            let identifier = token(identifier_token_index.checked_sub(1));
 
-            msg = format!(
-                "the value '{}' on line {}:{},  is not found in the current scope",
-                unknown_value_token.token_type.get_content().unwrap(),
-                unknown_value_token.line,
-                unknown_value_token.column
+            diagnostic.message = format!(
+                "the value {} is not found in the current scope",
+                quoted(identifier)
             );
-
-            error_line = format!(
-                "{} | {}",
-                unknown_value_token.line,
-                text_lines[unknown_value_token.line as usize - 1]
-            )
+            diagnostic.primary = identifier.map(|token| label(token, "not found"));
         }
-
-        //handling of UnexexpectedTokenError
-        ThorLangError::UnexpectedToken {
-            expected,
-            encountered,
+        //the resolver reports these before any code runs
+        ThorLangError::UseBeforeDeclaration {
+            identifier_token_index,
         } => {
-            let encountered_token = tokens[encountered - 1].clone();
-            let next_token = tokens[encountered].clone();
+            let identifier = token(identifier_token_index.checked_sub(1));
 
-            let expected_tokens = if (expected.len() > 1) {
-                "one of".to_string() + &format!("{:?}", expected).to_owned()
-            } else {
-                format!("{:?}", expected[0])
-            };
+            diagnostic.message = format!(
+                "the variable {} is used before it is declared",
+                quoted(identifier)
+            );
+            diagnostic.primary = identifier.map(|token| label(token, "used here"));
+            diagnostic.notes.push(
+                "variables can only be used after their let, functions declared later can use them"
+                    .to_string(),
+            );
+        }
+        ThorLangError::DuplicateDeclaration { name_token_index } => {
+            let name = token(Some(name_token_index));
 
-            msg = format!(
-                "expected {:?} after {:?} on line {:?}:{:?},\nfound {:?} instead",
-                expected_tokens,
-                encountered_token.token_type,
-                encountered_token.line,
-                encountered_token.column,
-                next_token.token_type
+            diagnostic.message = format!(
+                "the variable {} is already declared in this scope",
+                quoted(name)
             );
+            diagnostic.primary = name.map(|token| label(token, "declared again here"));
+            diagnostic
+                .notes
+                .push("assigning without let changes the existing variable".to_string());
+        }
+        ThorLangError::NotInLoop {
+            keyword_token_index,
+        } => {
+            let keyword = token(Some(keyword_token_index));
 
-            error_line = format!(
-                "{} | {}",
-                encountered_token.line,
-                text_lines[encountered_token.line as usize - 1]
-            )
+            diagnostic.message = format!(
+                "{} can only be used inside of a loop",
+                quoted(keyword)
+            );
+            diagnostic.primary = keyword.map(|token| label(token, "outside of a while or for loop"));
         }
         ThorLangError::IndexError {
             index_number_token_index,
             array_value,
             tried_index,
         } => {
-            let number_token = tokens[index_number_token_index].clone();
-            let array_token = tokens[index_number_token_index - 1].clone();
-
-            if let ValueType::Array(arr) = array_value.value {
-                let possible_length = arr.len();
-
-                msg = format!("array '{}' on line {}:{} only has length {}.\naccessing its {}. ({} + 1) element is not possible", 
-                    array_token.token_type.get_content().unwrap(),
-                    array_token.line, 
-                    array_token.column,
-                    possible_length, 
-                    tried_index + 1.0, 
-                    tried_index);
-
-                error_line = format!(
-                    "{} | {}",
-                    array_token.line,
-                    text_lines[array_token.line as usize - 1]
-                )
+            let lbrack = token(Some(index_number_token_index));
+            let array = token(index_number_token_index.checked_sub(1));
+
+            let length = match &array_value.value {
+                ValueType::Array(arr) => Some(arr.lock().len()),
+                ValueType::String(str) => Some(str.chars().count()),
+                _ => None,
+            };
+
+            diagnostic.message = match length {
+                Some(length) => format!(
+                    "the index {} is out of bounds for {} {} of length {}",
+                    tried_index,
+                    article(array_value.type_name()),
+                    array_value.type_name(),
+                    length
+                ),
+                None => format!(
+                    "the index {} cannot be used on {} {}",
+                    tried_index,
+                    article(array_value.type_name()),
+                    array_value.type_name()
+                ),
+            };
+            diagnostic.primary = lbrack.map(|token| label(token, "indexed here"));
+
+            if let Some(length) = length {
+                diagnostic
+                    .labels
+                    .extend(array.map(|token| label(token, &format!("has length {}", length))));
             }
+
+            diagnostic
+                .notes
+                .push("indices start at 0 and have to be whole numbers".to_string());
         }
         ThorLangError::FunctionArityError {
             function_paren_token,
-            needed_arguments_length,
+            accepted_arguments,
             arguments_length,
         } => {
-            let paren_token = tokens[function_paren_token].clone();
-
-            //-2 because i registered the RPAREN
-            //also every given argument also needs to be counted
-            //and if there is more than one then we need to also count the commas
-            //
-            //
-            let function_name_token = tokens[function_paren_token - 1].clone();
-
-            msg = format!(
-                "function '{}' on line {}:{}\nexpects {} arguments but got {}",
-                function_name_token.token_type.get_content().unwrap(),
-                paren_token.line,
-                paren_token.column,
-                needed_arguments_length,
+            let callee = token(function_paren_token.checked_sub(1));
+
+            diagnostic.message = format!(
+                "the function {} expects {} {} but got {}",
+                quoted(callee),
+                accepted_arguments,
+                arguments(accepted_arguments.max.unwrap_or(accepted_arguments.min)),
                 arguments_length
             );
+            diagnostic.primary = callee.map(|token| {
+                label(
+                    token,
+                    &format!(
+                        "called with {} {}",
+                        arguments_length,
+                        arguments(arguments_length)
+                    ),
+                )
+            });
+        }
+        ThorLangError::UnknownArgument {
+            function_paren_token,
+            name,
+        } => {
+            let callee = token(function_paren_token.checked_sub(1));
+
+            diagnostic.message = format!(
+                "the function {} has no parameter `{}`",
+                quoted(callee),
+                name
+            );
+            diagnostic.primary = callee.map(|token| label(token, &format!("`{}` is passed here", name)));
+        }
+        ThorLangError::DuplicateArgument {
+            function_paren_token,
+            name,
+        } => {
+            let callee = token(function_paren_token.checked_sub(1));
 
-            error_line = format!(
-                "{} | {}",
-                paren_token.line,
-                text_lines[paren_token.line as usize - 1]
+            diagnostic.message = format!("the argument `{}` is passed more than once", name);
+            diagnostic.primary = callee.map(|token| label(token, "in this call"));
+            diagnostic.notes.push(
+                "an argument can be passed by position or by name, but not both".to_string(),
             );
         }
         ThorLangError::OperationArityError {
@@ -142,98 +655,156 @@ pub fn handle_error(text: String, tokens: Vec<Token>, error: ThorLangError) {
             expected_arguments,
             provided_arguments,
         } => {
-            let op_token = tokens[operator_token_index - 1].clone();
+            let operator = token(operator_token_index.checked_sub(1));
 
-            msg = format!(
-                "the operator '{}' on line {}:{}\nexpects {} arguments but got {}",
-                stringify_token_type(op_token.token_type),
-                op_token.line,
-                op_token.column,
+            diagnostic.message = format!(
+                "the operator {} expects {} {} but got {}",
+                quoted(operator),
                 expected_arguments,
+                arguments(expected_arguments),
                 provided_arguments
             );
-
-            error_line = format!(
-                "{} | {}",
-                op_token.line,
-                text_lines[op_token.line as usize - 1]
-            );
+            diagnostic.primary = operator.map(|token| label(token, "used here"));
         }
         ThorLangError::ThorLangException {
             exception,
             throw_token_index,
         } => {
-            let throw_token = tokens[throw_token_index].clone();
-
-            msg = format!(
-                "the user has decided to throw {:?} on line {}:{} using the throw token",
-                stringify_value(*exception),
-                throw_token.line,
-                throw_token.column
-            );
-
-            error_line = format!(
-                "{} | {}",
-                throw_token.line,
-                text_lines[throw_token.line as usize - 1]
-            );
+            diagnostic.message = format!("uncaught exception {}", stringify_value(*exception));
+            diagnostic.primary = token(Some(throw_token_index)).map(|token| label(token, "thrown here"));
+            diagnostic
+                .notes
+                .push("exceptions can be caught with a try expression".to_string());
         }
         ThorLangError::RetrievalError {
             retrieve_seperator_token_index,
         } => {
-            let seperator_token = tokens[retrieve_seperator_token_index].clone();
-
-            let object_token = tokens[retrieve_seperator_token_index - 1].clone();
-
-            let key_token = tokens[retrieve_seperator_token_index + 1].clone();
+            let seperator = token(Some(retrieve_seperator_token_index));
+            let object = token(retrieve_seperator_token_index.checked_sub(1));
+            let key = token(retrieve_seperator_token_index.checked_add(1)).or(seperator);
+
+            diagnostic.message = format!(
+                "the object {} doesn't have a field {}",
+                quoted(object),
+                quoted(key)
+            );
+            diagnostic.primary = key.map(|token| label(token, "no such field"));
+            diagnostic.labels.extend(object.map(|token| label(token, "in this object")));
+        }
+        ThorLangError::EvalError {
+            operation_token_index,
+        } => {
+            let operation = token(Some(operation_token_index));
 
-            msg = format!(
-                "the object {} on line {}:{} doesn't have a field {}",
-                object_token.token_type.get_content().unwrap(),
-                seperator_token.line,
-                seperator_token.column,
-                key_token.token_type.get_content().unwrap()
+            diagnostic.message = format!(
+                "the operation {} cannot be performed on these values",
+                quoted(operation)
             );
+            diagnostic.primary = operation.map(|token| label(token, "not defined for these values"));
+            diagnostic
+                .notes
+                .push("overloading might help (overload + (a, b) { ... })".to_string());
+        }
+        ThorLangError::DivisionByZero {
+            operator_token_index,
+        } => {
+            let operation = token(Some(operator_token_index));
 
-            error_line = format!(
-                "{} | {}",
-                seperator_token.line,
-                text_lines[seperator_token.line as usize - 1]
+            diagnostic.message = format!(
+                "the operation {} divides by zero",
+                quoted(operation)
             );
+            diagnostic.primary = operation.map(|token| label(token, "the right side is 0"));
         }
-        ThorLangError::EvalError {
-            operation_token_index,
+        ThorLangError::DestructuringError {
+            token_index,
+            expected,
+            value,
         } => {
-            let left_op = tokens[operation_token_index - 1].clone();
-            let right_op = tokens[operation_token_index + 1].clone();
-            let operation_token = tokens[operation_token_index].clone();
+            diagnostic.message = format!(
+                "the value {} cannot be destructured, expected {}",
+                stringify_value(*value),
+                expected
+            );
+            diagnostic.primary = token(Some(token_index)).map(|token| label(token, "destructured here"));
+            diagnostic.notes.push(
+                "a default (like [a, b = nil]) can be given for elements and fields that might be missing"
+                    .to_string(),
+            );
+        }
+        ThorLangError::NonExhaustiveMatch {
+            match_token_index,
+            value,
+        } => {
+            diagnostic.message = format!(
+                "none of the arms of the match matches the value {}",
+                stringify_value(*value)
+            );
+            diagnostic.primary = token(Some(match_token_index)).map(|token| label(token, "no arm matches"));
+            diagnostic
+                .notes
+                .push("adding an arm with _ matches everything else".to_string());
+        }
+        ThorLangError::UnhashableKey {
+            dot_token_index,
+            key,
+        } => {
+            let type_name = key.type_name();
+            let key_token = token(dot_token_index.checked_add(1));
 
-            let left_type = stringify_token_type(left_op.token_type);
-            let right_type = stringify_token_type(right_op.token_type);
+            diagnostic.message = format!(
+                "{} {type_name} can't be used as a field name",
+                article(type_name)
+            );
+            diagnostic.primary = key_token.map(|token| label(token, &format!("this is {} {type_name}", article(type_name))));
+            diagnostic
+                .notes
+                .push("field names can be strings, numbers or bools".to_string());
+        }
+        //errors of natives, they don't point into the code
+        ThorLangError::RuntimeError { message } => {
+            diagnostic.message = message;
+        }
+        ThorLangError::UnknownError => {
+            diagnostic.message = "an unknown error occured".to_string();
+        }
+        //handled above
+        ThorLangError::Traced { .. } => {}
+    }
 
-            let op_type = stringify_token_type(operation_token.token_type);
+    let text_lines: Vec<&str> = text.split('\n').collect();
 
-            msg = format!("the operation {} on line {}:{}, cannot be performed on types {:?} and {:?} \noverloading might help", 
-                op_type,
-                operation_token.line,
-                operation_token.column,
-                left_type, 
-                right_type
-                );
+    for label in diagnostic.primary.iter().chain(diagnostic.labels.iter()) {
+        let code = (label.span.line as usize)
+            .checked_sub(1)
+            .and_then(|index| text_lines.get(index));
 
-            error_line = format!(
-                "{} | {}",
-                operation_token.line,
-                text_lines[operation_token.line as usize - 1]
-            );
+        if let Some(code) = code {
+            diagnostic
+                .source_lines
+                .insert(label.span.line, code.trim_end_matches('\r').to_string());
         }
+    }
+
+    return diagnostic;
+}
 
-        _ => println!("{:?}", error),
+//"argument" or "arguments" for the given count
+fn arguments(count: usize) -> &'static str {
+    if count == 1 {
+        return "argument";
     }
 
-    println!("\n{msg}\n");
-    println!("{error_line}\n");
-    //println!("{tip}");
+    return "arguments";
+}
+
+//the article in front of a type name (an array, a string)
+fn article(type_name: &str) -> &'static str {
+    if type_name.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        return "an";
+    }
+
+    return "a";
 }
 
 //returns the type of token that is wrong or that was expected
@@ -246,7 +817,7 @@ pub fn stringify_token_type(token_type: TokenType) -> &'static str {
         return "number";
     }
 
-    if let TokenType::STRING(str) = token_type {
+    if let TokenType::STRING(str) | TokenType::STRINGPART(str) = token_type {
         return "string";
     }
 
@@ -260,13 +831,18 @@ pub fn stringify_token_type(token_type: TokenType) -> &'static str {
         TokenType::RBRACK => "]", //right bracket : ]
         TokenType::LBRACE => "{", //left brace : {
         TokenType::RBRACE => "}", //right brace : }
+        TokenType::INTERPOLATIONEND => "} (end of interpolation)",
         TokenType::COMMA => "comma",
         TokenType::DOT => ".",
+        TokenType::COLON => ":",
         TokenType::MINUS => "- (minus)",
         TokenType::PLUS => "+ (plus)",
         TokenType::SEMICOLON => "semicolon ;",
         TokenType::SLASH => "/ (slash)",
         TokenType::STAR => "* (star)",
+        TokenType::PERCENT => "% (modulo)",
+        TokenType::TILDESLASH => "~/ (floor division)",
+        TokenType::STARSTAR => "** (power)",
         TokenType::BANG => "! (bang)",
         TokenType::BANGEQ => "!= (not equal)",
         TokenType::EQ => "= (assign)",
@@ -275,8 +851,18 @@ pub fn stringify_token_type(token_type: TokenType) -> &'static str {
         TokenType::GREATEREQ => ">= (greater or equal)",
         TokenType::LESS => "< (less than)",
         TokenType::LESSEQ => "<= (less or equal)",
+        TokenType::PLUSEQ => "+=",
+        TokenType::MINUSEQ => "-=",
+        TokenType::STAREQ => "*=",
+        TokenType::SLASHEQ => "/=",
+        TokenType::PERCENTEQ => "%=",
+
+        TokenType::AND => "and",
+        TokenType::OR => "or",
 
         TokenType::TRY => "try",
+        TokenType::MATCH => "match",
+        TokenType::FATARROW => "=>",
         TokenType::OVERLOAD => "overload",
         TokenType::DO => "do",
         TokenType::ELSE => "else",
@@ -289,7 +875,14 @@ pub fn stringify_token_type(token_type: TokenType) -> &'static str {
         TokenType::TRUE => "true",
         TokenType::LET => "let",
         TokenType::WHILE => "while",
+        TokenType::FOR => "for",
+        TokenType::IN => "in",
+        TokenType::TO => "to",
+        TokenType::ON => "on",
+        TokenType::STEP => "step",
         TokenType::THROW => "throw",
+        TokenType::BREAK => "break",
+        TokenType::CONTINUE => "continue",
 
         TokenType::EOF => "eof",
         _ => "unknown token",
@@ -1,256 +1,37 @@
 use crate::{
-    execute_lib_function, get_registered_function, hash_value, register_array_methods,
-    register_bool_methods, register_number_methods, register_string_methods,
+    call_site, compile_block, compile_expression, diagnose, execute_lib_function,
+    get_registered_function, get_registry, register_bool_methods, resolve, resolve_expression,
+    run_chunk, Span,
 };
 
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 
-use std::cell::RefCell;
 use std::collections::HashMap;
-use std::rc::Rc;
 
 use type_lib::*;
 
-//here the magic happpens: every list of statements mutates the env tree, and as soon as a branch
-//of the env tree is not needed it automatically disappears, meaning that we can only
+//statements are compiled to bytecode and run on the vm, the environment tree works as before:
+//every block mutates the env tree, and as soon as a branch of the env tree is not needed it
+//automatically disappears, meaning that we can only
 // - mutate variables that exist
 // - add variables to the currently used branch of the env tree
 //
 //this ensures that as soon as a branch is exited (i.e a block is done executing) we will have the
 //old values back.
+//
+//the code is resolved as if it ran in the outermost scope, so its own variables are declared by name
+//in the given environment
 pub fn eval_statement(
-    stmts: Vec<Statement>,
+    mut stmts: Vec<Statement>,
     enclosing: Arc<Mutex<Environment>>,
     overloadings: &mut Overloadings,
 ) -> Result<Value, ThorLangError> {
-    //evaluating statement by statement
-    for stmt in stmts {
-        match stmt {
-            //not implemented quite yet
-            Statement::Throw {
-                exception,
-                throw_token_index,
-            } => {
-                let exception = eval(&exception, enclosing, overloadings)?;
-
-                return Err(ThorLangError::ThorLangException {
-                    exception: Box::new(exception),
-                    throw_token_index,
-                });
-            }
-
-            //this works basically like a function, except that the call operation takes place in
-            //the corresponding eval function
-            Statement::Overload {
-                operator,
-                operands,
-                operation,
-                line: _,
-            } => {
-                let arity = operands.len();
-
-                //we dont want to mess with already defined overloaded operators, so any overlaoded
-                //operator will only be able to access before initialized (overloaded) operators,
-                //this means that similar to function environments we have to pass a operator env
-                let defined_overloadings = overloadings.clone();
-
-                let operator_info = OperationInfo {
-                    operands,
-                    operation,
-                    overloadings: defined_overloadings,
-                };
-
-                //if there is a list of overloadings for the given operator already we just push
-                //this overloading to it. Else we create such a list for use in the future
-                if let Some(operationlist) = overloadings.get_mut(&(operator.clone(), arity)) {
-                    operationlist.insert(0, operator_info);
-                } else {
-                    overloadings.insert((operator, arity), vec![operator_info]);
-                }
-            }
-            Statement::Return {
-                expression,
-                line: _,
-            } => {
-                let mut ret_value = eval(&expression, enclosing.clone(), overloadings)?;
-
-                //we want the ret_value to bubble up through the statements above, so we need to
-                //mark it as a return value
-                ret_value.return_true = true;
-
-                return Ok(ret_value);
-            }
-
-            Statement::Function {
-                name,
-                body,
-                arguments,
-                line: _,
-            } => {
-                let closure = Environment::new(Some(enclosing.clone()));
-
-                let function = Value::thor_function(name.clone(), arguments, *body, closure.clone());
-
-                //insert the function with its name into the environment
-                enclosing
-                    .lock()
-                    .unwrap()
-                    .values
-                    .lock().unwrap()
-                    .insert(name.clone(), function.clone());
-
-                //insert the function with its name into the closure to allow for recursion
-                closure
-                    .lock()
-                    .unwrap()
-                    .values
-                    .lock().unwrap()
-                    .insert(name, function);
-            }
-            //a block just opens a new env tree branch
-            Statement::Block {
-                statements,
-                line: _,
-            } => {
-                let local_scope = Environment::new(Some(enclosing.clone()));
-                eval_statement(statements, local_scope.clone(), overloadings)?;
-            }
-
-            //if statements are one to one in the host language
-            Statement::If {
-                condition,
-                then_branch,
-                else_branch,
-                line: _,
-            } => {
-                if let ValueType::Bool(bool) =
-                    eval(&condition, enclosing.clone(), overloadings)?.value
-                {
-                    if bool {
-                        let return_val =
-                            eval_statement(*then_branch, enclosing.clone(), overloadings)?;
-
-                        //when we encounter a value with return_true = true, we need to bubble the
-                        //value up
-                        if return_val.return_true {
-                            return Ok(return_val);
-                        }
-                    } else {
-                        if let Some(ref _else_block) = else_branch {
-                            let return_val = eval_statement(
-                                *else_branch.unwrap(),
-                                enclosing.clone(),
-                                overloadings,
-                            )?;
-
-                            //same again
-                            if return_val.return_true {
-                                return Ok(return_val);
-                            }
-                        }
-                    }
-                }
-            }
-
-            //while statements are as well, we ned a "return_true" value however to detect when we
-            //need to return a value
-            Statement::While {
-                condition,
-                block,
-                line: _,
-            } => {
-                let mut condition_value =
-                    eval(&condition.clone(), enclosing.clone(), overloadings)?;
-                while let ValueType::Bool(bool) = condition_value.value {
-                    if bool {
-                        let return_val =
-                            eval_statement(*block.clone(), enclosing.clone(), overloadings)?;
-                        condition_value =
-                            eval(&condition.clone(), enclosing.clone(), overloadings)?;
-
-                        //if return_true is true that means that the above eval function has hit a
-                        //return statement and we need to return the value here
-                        if return_val.return_true {
-                            return Ok(return_val);
-                        }
-                    } else {
-                        condition_value.value = ValueType::Nil
-                    }
-                }
-            }
-            Statement::For {
-                iterator,
-                iteration_variable,
-                block,
-            } => {
-                let iter = eval(&iterator, enclosing.clone(), overloadings)?;
-
-                if let ValueType::Array(arr) = iter.value {
-                    for val in arr.iter() {
-                        let intermediate_environment = Environment::new(Some(enclosing.clone()));
-
-                        let var_name = iteration_variable.get_content().unwrap();
-
-                        intermediate_environment
-                            .lock()
-                            .unwrap()
-                            .values
-                            .lock().unwrap()
-                            .insert(var_name.clone(), val.clone());
-
-                        let ret_val =
-                            eval_statement(block.clone(), intermediate_environment, overloadings)?;
-
-                        if ret_val.return_true {
-                            return Ok(ret_val.clone());
-                        }
-                    }
-                }
-            }
-
-            //print is built in, but can also be used with the printfunction
-            Statement::Print {
-                expression,
-                line: _,
-            } => {
-                let result = eval(&expression, enclosing.clone(), overloadings)?;
-
-                //println!("debug mode {:#?}", result);
-                if let ValueType::String(ref str) = result.value {
-                    println!("{str}");
-                } else {
-                    println!("{}", stringify_value(result));
-                }
-            }
-
-            Statement::Do {
-                expression,
-                line: _,
-            } => {
-                //runs expressions
-                eval(&expression, enclosing.clone(), overloadings)?;
-            }
+    resolve(&mut stmts, &HashSet::new())?;
 
-            //variable declaration only ever mutates the current branch of the env tree ensuring,
-            //in this case "enclosing"
-            Statement::Variable {
-                name,
-                expression,
-                line: _,
-            } => {
-                let val = eval(&expression, enclosing.clone(), overloadings)?;
-
-                enclosing
-                    .lock()
-                    .unwrap()
-                    .values
-                    .lock().unwrap()
-                    .insert(name, val);
-            }
-        }
-    }
+    let chunk = compile_block(&stmts)?;
 
-    Ok(Value::default())
+    run_chunk(Arc::new(chunk), enclosing, overloadings)
 }
 
 pub fn eval_function(function_value : Value, arguments: Vec<Value>, enclosing: Arc<Mutex<Environment>>, overloadings: &mut Overloadings) -> Result<Value, ThorLangError>{
@@ -266,13 +47,17 @@ pub fn eval_function(function_value : Value, arguments: Vec<Value>, enclosing: A
 
     let function_name = function.get_function_name();
 
-    if needed_arguments.len() != arguments.len(){
-        return ThorLangError::runtime_error(format!("arguments of function {function_name} are {} but need to be {}", arguments.len(), needed_arguments.len()))
+    let arity = function.arity();
+
+    if !arity.accepts(arguments.len()){
+        return ThorLangError::runtime_error(format!("arguments of function {function_name} are {} but need to be {}", arguments.len(), arity))
     }
 
+    let bound = bind_arguments(&needed_arguments, arity, arguments, &[], 0)?;
 
-    let mut arguments : HashMap<String, Value> = needed_arguments.into_iter().enumerate().map(|(index, key)|{
-        (key.to_string(), arguments[index].clone()) 
+    //arguments that were left out are not in the map, their defaults fill them in
+    let mut arguments : HashMap<String, Value> = needed_arguments.into_iter().zip(bound).filter_map(|(key, value)|{
+        Some((key, value?)) 
     }).collect();
 
     
@@ -291,19 +76,29 @@ pub fn eval_function(function_value : Value, arguments: Vec<Value>, enclosing: A
                 function_value, arguments, enclosing.clone(), overloadings
             )
         }
-        Function::ThorFunction { name, body, needed_arguments, closure } => {
+        Function::ThorFunction { name, body, needed_arguments, closure, .. } => {
             
-            let closure = Environment::new(Some(enclosing.clone()));
+            //the arguments are the first slots of the environment the body runs in
+            let function_env = Environment::new(Some(closure));
    
-            arguments.iter().for_each(|(key, value)|{  
-                closure.lock().unwrap().values.lock().unwrap().insert(key.to_string(), value.clone()); 
+            needed_arguments.iter().enumerate().for_each(|(index, key)|{  
+                if let Some(value) = arguments.remove(key){
+                    function_env.lock().unwrap().define_at(index, key, value); 
+                }
             });
 
 
-            eval_statement(body, closure, overloadings)
+            //functions called by natives (like listeners) have no call site in the code
+            let call = TraceFrame {
+                function : name,
+                call_token_index : None,
+                source : None
+            };
+
+            run_chunk(body.clone(), function_env, overloadings).map_err(|err| err.traced(body.source.clone(), vec![call]))
         }
-        Function::NamedFunction { name, needed_arguments, self_value, env_state, var_name } => {
-            let function = get_registered_function(name)?;
+        Function::NamedFunction { name, needed_arguments, self_value, env_state } => {
+            let function = get_registered_function(&enclosing, name, self_value.as_deref())?;
             
 
             let mut s_value = None;
@@ -318,22 +113,80 @@ pub fn eval_function(function_value : Value, arguments: Vec<Value>, enclosing: A
                 arguments,
                 s_value,
                 Some(enclosing.clone()),
-                var_name,
                 env_state,
             )
         }
     }
 }
 
+//puts the arguments of a call in the order of the parameters (shared with the vm), the last ones are
+//passed by the given names. parameters that get no argument are none so their default can fill
+//them in, a rest parameter gets the positional arguments that are left as an array
+pub(crate) fn bind_arguments(
+    parameters: &[String],
+    arity: Arity,
+    mut arguments: Vec<Value>,
+    names: &[String],
+    paren_token_index: usize,
+) -> Result<Vec<Option<Value>>, ThorLangError> {
+    let arguments_length = arguments.len();
+
+    let arity_error = || ThorLangError::FunctionArityError {
+        function_paren_token: paren_token_index,
+        accepted_arguments: arity,
+        arguments_length,
+    };
+
+    let named = arguments.split_off(arguments_length - names.len());
+
+    let rest = arity.max.is_none();
+    let positional_parameters = parameters.len() - rest as usize;
+
+    if !rest && arguments.len() > positional_parameters {
+        return Err(arity_error());
+    }
+
+    let mut positional = arguments.into_iter();
+
+    let mut bound: Vec<Option<Value>> = (0..positional_parameters).map(|_| positional.next()).collect();
+
+    if rest {
+        bound.push(Some(Value::array(positional.collect())));
+    }
+
+    for (name, value) in names.iter().zip(named) {
+        let index = parameters[..positional_parameters].iter().position(|parameter| parameter == name);
+
+        match index {
+            Some(index) if bound[index].is_none() => bound[index] = Some(value),
+            Some(_) => return Err(ThorLangError::DuplicateArgument {
+                function_paren_token: paren_token_index,
+                name: name.to_string(),
+            }),
+            None => return Err(ThorLangError::UnknownArgument {
+                function_paren_token: paren_token_index,
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    //only the parameters with a default can be left out
+    if bound[..arity.min].iter().any(Option::is_none) {
+        return Err(arity_error());
+    }
+
+    return Ok(bound);
+}
+
 //helper function to check whether or not a operation works for the inputs provided
+//
+//the operation runs like a function in the environment the overloading was declared in, with the
+//operands in the first slots
 fn eval_overloaded(
     operation_list: Vec<OperationInfo>,
     arguments: Vec<Value>,
-    enclosing: Arc<Mutex<Environment>>,
     operator_token_index: usize,
 ) -> Result<Value, ThorLangError> {
-    let op_env = enclosing.lock().unwrap().clone();
-
     //loops over every operation associated with the given operator sign
     //when they throw we move on to the next one
     for mut op in operation_list {
@@ -349,19 +202,16 @@ fn eval_overloaded(
             );
         }
 
+        let op_env = Environment::new(Some(op.closure));
+
         for i in 0..operands.len() {
             op_env
-                .values
                 .lock()
                 .unwrap()
-                .insert(operands[i].clone(), arguments[i].clone());
+                .define_at(i, &operands[i], arguments[i].clone());
         }
 
-        let tried_eval = eval_statement(
-            operation,
-            Arc::new(Mutex::new(op_env.clone())),
-            overloadings,
-        );
+        let tried_eval = run_chunk(operation, op_env, overloadings);
 
         if let Ok(result) = tried_eval {
             return Ok(result);
@@ -373,17 +223,13 @@ fn eval_overloaded(
     ThorLangError::eval_error(operator_token_index)
 }
 
-//order of precedence is as follows
-// eval_statement -> eval -> eval_binary -> eval_unary -> eval_literal
-fn eval_unary(
+//applies a unary operator to an already evaluated value (shared with the vm)
+pub(crate) fn unary_operation(
     operator: TokenType,
-    right: &Expression,
-    enclosing: Arc<Mutex<Environment>>,
+    r: Value,
     overloadings: &mut Overloadings,
     operator_token_index: usize,
 ) -> Result<Value, ThorLangError> {
-    let r = eval(right, enclosing.clone(), overloadings)?;
-
     //first we try to eval the overloadings if there are any
 
     //else we just return the normally evaluated value
@@ -415,7 +261,6 @@ fn eval_unary(
         if let Ok(result) = eval_overloaded(
             operation_info.to_vec(),
             vec![r.clone()],
-            enclosing.clone(),
             operator_token_index,
         ) {
             return Ok(result);
@@ -429,7 +274,7 @@ fn eval_unary(
 
 //evaluates the "atoms" these can not be further reduced and bubble up to form more complex data
 //(not types but composed values like 1 + 2)
-fn eval_literal(literal: TokenType, literal_token_index: usize) -> Result<Value, ThorLangError> {
+pub(crate) fn eval_literal(literal: TokenType, literal_token_index: usize) -> Result<Value, ThorLangError> {
     //turn literaltype into value wrapped in value_type
     match literal {
         TokenType::NIL => return Ok(Value::nil()),
@@ -457,17 +302,47 @@ fn eval_literal(literal: TokenType, literal_token_index: usize) -> Result<Value,
     }
 }
 
-//all the possible binary operation combinations.
-fn eval_binary(
-    left: &Expression,
+//applies a binary operator to two already evaluated values (shared with the vm)
+pub(crate) fn binary_operation(
+    l: Value,
     operator: TokenType,
-    right: &Expression,
-    enclosing: Arc<Mutex<Environment>>,
+    r: Value,
     overloadings: &mut Overloadings,
     operator_token_index: usize,
 ) -> Result<Value, ThorLangError> {
-    let l = eval(left, enclosing.clone(), overloadings)?;
-    let r = eval(right, enclosing.clone(), overloadings)?;
+    //numbers are by far the most common operands, so they are handled without any copies
+    if let (ValueType::Number(l), ValueType::Number(r)) = (&l.value, &r.value) {
+        let (l, r) = (*l, *r);
+
+        match operator {
+            TokenType::PLUS => return Ok(Value::number(l + r)),
+            TokenType::MINUS => return Ok(Value::number(l - r)),
+            TokenType::STAR => return Ok(Value::number(l * r)),
+            TokenType::SLASH => return Ok(Value::number(l / r)),
+            TokenType::STARSTAR => return Ok(Value::number(l.powf(r))),
+            //both round down, so the remainder has the sign of the right side (-1 % 256 is 255)
+            TokenType::PERCENT | TokenType::TILDESLASH if r == 0.0 => {
+                return Err(ThorLangError::DivisionByZero {
+                    operator_token_index,
+                })
+            }
+            TokenType::PERCENT => {
+                let remainder = l % r;
+
+                if remainder != 0.0 && (remainder < 0.0) != (r < 0.0) {
+                    return Ok(Value::number(remainder + r));
+                }
+
+                return Ok(Value::number(remainder));
+            }
+            TokenType::TILDESLASH => return Ok(Value::number((l / r).floor())),
+            TokenType::LESSEQ => return Ok(Value::bool(l <= r)),
+            TokenType::LESS => return Ok(Value::bool(l < r)),
+            TokenType::GREATEREQ => return Ok(Value::bool(l >= r)),
+            TokenType::GREATER => return Ok(Value::bool(l > r)),
+            _ => (),
+        }
+    }
 
     let l_copy = l.clone();
     let r_copy = r.clone();
@@ -532,9 +407,9 @@ fn eval_binary(
         }
         TokenType::STEP => {
             if let (ValueType::Array(arr), ValueType::Number(step)) = (l.value, r.value) {
-                let new_arr = arr.iter().step_by(step as usize).map(|x| x.to_owned());
+                let new_arr = arr.lock().iter().step_by(step as usize).map(|x| x.to_owned()).collect();
 
-                return Ok(Value::array(new_arr.collect()));
+                return Ok(Value::array(new_arr));
             }
         }
 
@@ -557,7 +432,6 @@ fn eval_binary(
         if let Ok(result) = eval_overloaded(
             op_overloadings.to_vec(),
             op_vec,
-            enclosing.clone(),
             operator_token_index,
         ) {
             return Ok(result);
@@ -567,576 +441,490 @@ fn eval_binary(
     return Ok(Value::default());
 }
 
-pub fn eval(
-    expr: &Expression,
+//joins the parts of an interpolated string (shared with the vm). strings are taken as they are and
+//everything else is stringified like print does, except for arrays and objects: they are turned
+//into a string by their to_string method or by an overloaded + (string + value) when there is one
+pub(crate) fn interpolate(
+    parts: Vec<Value>,
     enclosing: Arc<Mutex<Environment>>,
     overloadings: &mut Overloadings,
+    string_token_index: usize,
 ) -> Result<Value, ThorLangError> {
-    //recursivley traverses the expr tree.
-    match expr {
-        //retrieve has to work for arrays like this array[number];
-        //and for objects like this object[key];
-        Expression::Retrieve {
-            retrievee,
-            key,
-            lbrack_token_index,
-        } => {
-            let key = eval(key, enclosing.clone(), overloadings)?;
+    let mut string = String::new();
 
-            let retrievee = eval(retrievee, enclosing.clone(), overloadings)?;
+    for part in parts {
+        match &part.value {
+            ValueType::String(part) => string += part,
+            ValueType::Array(_) | ValueType::Object(_) => {
+                if let Some(to_string) = part.get_field("to_string") {
+                    let converted =
+                        eval_function(to_string, vec![], enclosing.clone(), overloadings)?;
 
-            let mut ret_val = Value::nil();
-
-            match (retrievee.value.clone(), key.value) {
-                //the case of array and number
-                (ValueType::Array(arr), ValueType::Number(num)) => {
-                    if num.round() != num {
-                        return ThorLangError::index_error(
-                            lbrack_token_index.clone(),
-                            retrievee,
-                            num,
-                        );
-                    }
-                    if let Some(el) = arr.get(num as usize) {
-                        ret_val = el.clone();
-                    } else {
-                        return ThorLangError::index_error(
-                            lbrack_token_index.clone(),
-                            retrievee,
-                            num,
-                        );
-                    }
+                    string += &stringify_value(converted);
+                    continue;
                 }
-                (ValueType::String(str), ValueType::Number(num)) => {
-                    if num.round() != num {
-                        return ThorLangError::index_error(
-                            lbrack_token_index.clone(),
-                            retrievee,
-                            num,
-                        );
-                    }
 
-                    if let Some(char) = str.chars().nth(num as usize) {
-                        ret_val = Value::string(char.to_string());
-                    } else {
-                        return ThorLangError::index_error(
-                            lbrack_token_index.clone(),
-                            retrievee,
-                            num,
-                        );
-                    }
-                }
-                //the case of object and string
-                (ValueType::Object, ValueType::String(str)) => {
-                    if let Some(val) = retrievee.clone().fields.get(&str) {
-                        ret_val = (val.clone());
-                    } else {
-                        ret_val = (Value::nil());
+                if let Some(operation_info) = overloadings.get(&(TokenType::PLUS, 2)) {
+                    let appended = eval_overloaded(
+                        operation_info.to_vec(),
+                        vec![Value::string(string.clone()), part.clone()],
+                        string_token_index,
+                    );
+
+                    if let Ok(Value {
+                        value: ValueType::String(appended),
+                        ..
+                    }) = appended
+                    {
+                        string = appended;
+                        continue;
                     }
                 }
-                _ => {
-                    return ThorLangError::unknown_value_error(lbrack_token_index + 1)
-                }
-            }
 
-
-            ret_val.library = retrievee.library;
-
-            return Ok(ret_val)
+                string += &stringify_value(part);
+            }
+            _ => string += &stringify_value(part),
         }
+    }
 
-        Expression::Lambda { block, arguments } => {
-            let closure = Environment::new(Some(enclosing.clone()));
-            let con_func = Value::thor_function(
-                "#lambda#".to_string(),
-                arguments.to_vec(),
-                block.to_vec(),
-                closure
-            );
-
-            Ok(con_func)
-        },
+    eval_literal(TokenType::STRING(string), string_token_index)
+}
 
-        Expression::Try { block } => {
-            //this ensures that errors can be returned as values
-            let eval_value = eval_statement(block.to_vec(), enclosing.clone(), overloadings);
-            return match eval_value {
-                Ok(val) => Ok(val),
-                Err(err) => {
-                    let err = Value::error(err);
-                    return Ok(err);
-                }
-            };
-        }
-        Expression::On {
-            block,
-            variables,
-            on_token_index,
-        } => {
-            //listeners here
+//retrieves the element at the key from arrays, strings and objects (shared with the vm)
+pub(crate) fn retrieve_value(
+    retrievee: Value,
+    key: Value,
+    lbrack_token_index: usize,
+) -> Result<Value, ThorLangError> {
+    let mut ret_val = Value::nil();
 
-            if let Some(block) = block {
+    match (&retrievee.value, &key.value) {
+        //the case of array and number
+        (ValueType::Array(arr), ValueType::Number(num)) => {
+            let num = *num;
+            if num.round() != num {
+                return ThorLangError::index_error(lbrack_token_index, retrievee, num);
+            }
+            let element = arr.lock().get(num as usize).cloned();
 
+            if let Some(el) = element {
+                ret_val = el;
+            } else {
+                return ThorLangError::index_error(lbrack_token_index, retrievee, num);
+            }
+        }
+        (ValueType::String(str), ValueType::Number(num)) => {
+            let num = *num;
+            if num.round() != num {
+                return ThorLangError::index_error(lbrack_token_index, retrievee, num);
+            }
 
-                for variable in variables {
-                    if let Expression::Identifier { name, identifier_token_index } = variable{
-                        enclosing.lock().unwrap().add_listener(
-                            name.to_string(),
-                            block.clone(),
-                            *on_token_index,
-                        )?;
+            if let Some(char) = str.chars().nth(num as usize) {
+                ret_val = Value::string(char.to_string());
+            } else {
+                return ThorLangError::index_error(lbrack_token_index, retrievee, num);
+            }
+        }
+        //the case of object and string
+        (ValueType::Object(obj), ValueType::String(str)) => {
+            if let Some(val) = obj.lock().get(str) {
+                ret_val = val.clone();
+            } else {
+                ret_val = Value::nil();
+            }
+        }
+        _ => return ThorLangError::unknown_value_error(lbrack_token_index + 1),
+    }
 
-                    }
-                    
-                    let new_listener = Value::thor_function("#lambda#".to_string(), vec![], block.to_vec(), enclosing.clone());
+    ret_val.library = retrievee.library;
 
-                    let mut value = eval(variable, enclosing.clone(), overloadings)?;
+    Ok(ret_val)
+}
 
+//returns the field with the given name, or the method of the prototype (shared with the vm)
+pub(crate) fn field_value(
+    callee_value: Value,
+    key_string: String,
+    enclosing: Arc<Mutex<Environment>>,
+) -> Result<Value, ThorLangError> {
+    //the default value is nil (field does not exist)
 
-                    if let Some(add_func) = value.fields.get_mut("add_listener"){
-                        
-                        add_func.library = value.library;
+    let mut ret_val = Value::default();
 
+    //if a field with the above name does exist we return it
+    if let Some(mut field) = callee_value.get_field(&key_string) {
+        if let Some(library) = callee_value.library {
+            field.library = Some(library.clone());
+        }
 
-                        eval_function(add_func.clone(), vec![new_listener], enclosing.clone(), overloadings)?;
-                    }
+        return Ok(field);
+    }
+    //else we try to return a value or method of the prototype
+    //depending on whether the value we want to call from the prototype method map
+    //(init_prototype_fields)
+    match &callee_value.value {
+        //the methods are registered when the interpreter is built
+        ValueType::String(_) | ValueType::Number(_) | ValueType::Array(_) => {
+            let registry = get_registry(&enclosing)?;
+            let method = registry.lock().unwrap().method(&callee_value, &key_string);
 
-                }
-                
-                return Ok(Value::nil());
+            if let Some(method) = method {
+                ret_val = method;
             }
+        }
 
-            ThorLangError::eval_error(*on_token_index)
+        //errors caught by try can be inspected
+        ValueType::Error(err) => {
+            ret_val = error_field(err, &key_string);
         }
 
-        Expression::FieldCall {
-            callee,
-            key,
-            dot_token_index: _,
-        } => {
-            //getting with the . (object.field)
+        //not finished yet, but can be at every moment
+        _ => (),
+    }
 
-            //first we need to get the key and the field we want to call from
-            let callee_value = eval(callee, enclosing.clone(), overloadings)?;
+    //if still no fields with the given name are found we return nil
 
-            let key_string: String;
+    ret_val.library = callee_value.library;
 
-            //if the key is an identifier we turn it to a string else we would hash it (hashing
-            //does not work, thinking about removing this feature)
-            if let Expression::Identifier {
-                name,
-                identifier_token_index: _,
-            } = *(*key).clone()
-            {
-                key_string = name;
-            } else {
-                key_string = hash_value(eval(key, enclosing.clone(), overloadings)?);
-            }
-            
+    Ok(ret_val)
+}
 
-            //the default value is nil (field does not exist)
+//the fields of an error value, the message, line and column are the ones of the diagnostic (line
+//and column are nil for errors without a place in the code) and value is what was thrown. the
+//message of a thrown value is the value itself
+fn error_field(err: &ThorLangError, key: &str) -> Value {
+    let diagnostic = || match err {
+        ThorLangError::Traced {
+            source: Some(source),
+            ..
+        } => diagnose(&source.text, &source.tokens, err.clone()),
+        _ => diagnose("", &[], err.clone()),
+    };
 
-            let mut ret_val = Value::default();
+    let position = |position: fn(&Span) -> i32| {
+        diagnostic()
+            .primary
+            .map(|label| Value::number(position(&label.span) as f64))
+            .unwrap_or_default()
+    };
 
-            //if a field with the above name does exist we return it
-            if let Some(field) = callee_value.fields.get(&key_string) {
-                
-                
-                let mut field = field.clone();
-                if let Some(library) = callee_value.library{
+    return match key {
+        "kind" => Value::str(err.kind()),
+        "message" => match err.untraced() {
+            ThorLangError::ThorLangException { exception, .. } => match &exception.value {
+                ValueType::String(message) => Value::string(message.clone()),
+                _ => Value::string(stringify_value(*exception.clone())),
+            },
+            _ => Value::string(diagnostic().message),
+        },
+        "line" => position(|span| span.line),
+        "column" => position(|span| span.column),
+        "value" => match err.untraced() {
+            ThorLangError::ThorLangException { exception, .. } => *exception.clone(),
+            _ => Value::nil(),
+        },
+        "trace" => trace_value(err),
+        _ => Value::nil(),
+    };
+}
 
-                    field.library = Some(library.clone());
-                }
+//the trace of an error as an array of objects holding the function, file, line and column of every
+//call (nil when unknown), the innermost call comes first
+fn trace_value(err: &ThorLangError) -> Value {
+    let ThorLangError::Traced { source, trace, .. } = err else {
+        return Value::array(vec![]);
+    };
 
-                //field.library = callee_value.library;
+    let calls = trace
+        .iter()
+        .map(|frame| {
+            let site = call_site(frame, source.as_deref());
+            let number = |number: Option<i32>| number.map(|number| Value::number(number as f64));
+
+            Value::object(HashMap::from([
+                ("function", Value::string(site.function)),
+                ("file", site.file.map(Value::string).unwrap_or_default()),
+                ("line", number(site.span.map(|span| span.line)).unwrap_or_default()),
+                ("column", number(site.span.map(|span| span.column)).unwrap_or_default()),
+            ]))
+        })
+        .collect();
+
+    return Value::array(calls);
+}
 
-                return Ok(field);
+//tests a value against the pattern of a match arm (shared with the vm), the values of the names
+//the pattern binds are pushed in the order of Pattern::bindings
+pub(crate) fn match_pattern(pattern: &Pattern, value: &Value, bindings: &mut Vec<Value>) -> bool {
+    match pattern {
+        Pattern::Wildcard => return true,
+        Pattern::Binding { .. } => {
+            bindings.push(value.clone());
+            return true;
+        }
+        //only the values are compared, literals come with methods that other values don't have
+        Pattern::Literal {
+            literal,
+            literal_token_index,
+        } => {
+            return eval_literal(literal.clone(), *literal_token_index)
+                .is_ok_and(|literal| literal.value == value.value);
+        }
+        Pattern::Type { type_name, binding } => {
+            if value.type_name() != type_name {
+                return false;
             }
-            //else we try to return a value or method of the prototype
-            //depending on whether the value we want to call from the prototype method map
-            //(init_prototype_fields)
-            match callee_value.value.clone() {
-                ValueType::String(_str) => {
-                    if let Some(field) =
-                        register_string_methods(callee_value.clone()).get(&key_string)
-                    {
-                        ret_val = field.clone();
-                    }
-                }
-                ValueType::Number(num) => {
-                    if let Some(field) =
-                        register_number_methods(callee_value.clone()).get(&key_string)
-                    {
-                        ret_val = field.clone()
-                    }
-                }
-                ValueType::Array(arr) => {
-                    let mut var_name = "".to_string();
 
-                    if let Expression::Identifier {
-                        name,
-                        identifier_token_index,
-                    } = *callee.clone()
-                    {
-                        var_name = name;
-                    }
+            return binding
+                .as_ref()
+                .map_or(true, |binding| match_pattern(binding, value, bindings));
+        }
+        Pattern::Array { elements, rest } => {
+            //the elements are copied out first, so the array isn't locked while they are matched
+            let Some(array) = value.to_arr() else {
+                return false;
+            };
 
-                    if let Some(field) =
-                        register_array_methods(callee_value.clone(), var_name).get(&key_string)
-                    {
-                        ret_val = field.clone();
-                    }
-                }
+            let fits = match rest {
+                Some(_) => array.len() >= elements.len(),
+                None => array.len() == elements.len(),
+            };
 
-                //not finished yet, but can be at every moment
-                _ => (),
+            if !fits {
+                return false;
             }
 
-            //if still no fields with the given name are found we return nil
-
-            ret_val.library = callee_value.library;
-
+            for (element, value) in elements.iter().zip(&array) {
+                if !match_pattern(element, value, bindings) {
+                    return false;
+                }
+            }
 
-            Ok(ret_val)
+            return rest.as_ref().map_or(true, |rest| {
+                match_pattern(rest, &Value::array(array[elements.len()..].to_vec()), bindings)
+            });
         }
+        Pattern::Object { keys, values } => {
+            let Some(object) = value.to_ob() else {
+                return false;
+            };
 
-        Expression::Array { values } => {
-            //calculates the value of an array (simply a value with the type array itself)
-            let mut value_array: Vec<Value> = vec![];
-
-            for value_expression in values {
-                let value = eval(value_expression, enclosing.clone(), overloadings)?;
-
-                value_array.push(value);
+            for (key, pattern) in keys.iter().zip(values) {
+                match object.get(key) {
+                    Some(field) if match_pattern(pattern, field, bindings) => (),
+                    _ => return false,
+                }
             }
 
-            Ok(Value::array(value_array))
+            return true;
         }
-        Expression::Object { keys, values } => {
-            let mut value_object = Value::nil();
-            value_object.value = ValueType::Object;
-
-            for i in 0..keys.len() {
-                let key = eval(&keys[i].clone(), enclosing.clone(), overloadings)?;
-                let value = eval(&values[i].clone(), enclosing.clone(), overloadings)?;
-
-                let key_string = stringify_value(key.clone());
+        //defaults only appear in destructuring patterns
+        Pattern::Default { pattern, .. } => return match_pattern(pattern, value, bindings),
+    }
+}
 
+//reads the current value of an assignment target (for compound assignments), fields that don't
+//exist yet are nil
+pub(crate) fn read_target(
+    order: &[(FieldKey, usize)],
+    slot: Option<Slot>,
+    enclosing: &Arc<Mutex<Environment>>,
+    eq_token_index: usize,
+) -> Result<Value, ThorLangError> {
+    let name = order[0].0.get_string().unwrap();
 
+    let variable = match slot {
+        Some(Slot { depth, index }) => enclosing.lock().unwrap().get_at(depth, index),
+        None => enclosing.lock().unwrap().get(&name),
+    };
 
-                value_object.fields.insert(key_string, value);
-            }
+    let mut current = match variable {
+        Some(variable) => variable,
+        None => return ThorLangError::eval_error(eq_token_index),
+    };
 
-            Ok(value_object)
-        }
-        Expression::Call {
-            callee,
-            paren_token_index,
-            arguments,
-        } => {
-            //to evaluate functions we need to distinguish between native functions and thor
-            //functions
-            let function = eval(callee, enclosing.clone(), overloadings)?;
-
-            //copy all overloadings to the environment
-            enclosing.lock().unwrap().set_overloadings(overloadings.clone());
-
-            if let ValueType::Function(Function::LibFunction {
-                name,
-                needed_arguments,
-                self_value,
-                mutating,
-            }) = &function.value
-            {
-                if needed_arguments.len() != arguments.len() {
-                    return ThorLangError::function_arity_error(
-                        paren_token_index.clone(),
-                        needed_arguments.len(),
-                        arguments.len(),
-                    );
+    for (key, key_index) in &order[1..] {
+        let next = match (&current.value, key) {
+            (ValueType::Array(arr), FieldKey::Int(num)) => match arr.lock().get(*num as usize) {
+                Some(element) => element.clone(),
+                None => {
+                    return ThorLangError::index_error(*key_index, current.clone(), *num as f64)
                 }
+            },
+            (_, FieldKey::String(key)) => current.get_field(key).unwrap_or_default(),
+            _ => Value::nil(),
+        };
 
+        current = next;
+    }
 
-                let mut eval_args: HashMap<String, Value> = HashMap::new();
+    return Ok(current);
+}
 
-                for i in 0..arguments.len() {
-                    let arg = eval(arguments.get(i).unwrap(), enclosing.clone(), overloadings)?;
-                    let arg_name = needed_arguments.get(i).unwrap();
+//assigns the value to the target and runs the listeners of the variable, the target is given as
+//its field order (a vector of keys, can be numbers for arrays or strings for objects), starting with
+//the name of the variable
+//
+//obj.hello[0]["hello"] turns to [obj, "hello", 0, "hello"]
+//
+//the variable is found by its slot when the resolver gave it one and by its name otherwise
+pub(crate) fn assign_value(
+    order: Vec<(FieldKey, usize)>,
+    slot: Option<Slot>,
+    eval_value: Value,
+    eq_token_index: usize,
+    enclosing: Arc<Mutex<Environment>>,
+    overloadings: &mut Overloadings,
+) -> Result<Value, ThorLangError> {
+    //iteratively go over the fields (creating them when they do not exist) and putting in
+    //the value at the deepest level
 
-                    eval_args.insert(arg_name.to_string(), arg);
-                }
+    let name = order.get(0).unwrap().0.get_string().unwrap();
 
-                if let Some(self_value) = self_value{
-                    eval_args.insert("self_value".to_string(), *self_value.clone());
-                }
+    if order.len() == 1 {
+        let set_val = set_variable(&enclosing, &name, slot, eval_value.clone(), eq_token_index)?;
 
-                return execute_lib_function(function, eval_args, enclosing, overloadings);
-            }
+        run_listeners(set_val, &enclosing, overloadings);
 
-            if let ValueType::Function(Function::NamedFunction {
-                name,
-                needed_arguments,
-                self_value,
-                env_state,
-                var_name,
-            }) = function.clone().value
-            {
-                if needed_arguments.len() != arguments.len() {
-                    return ThorLangError::function_arity_error(
-                        paren_token_index.clone(),
-                        needed_arguments.len(),
-                        arguments.len(),
-                    );
-                }
+        return Ok(eval_value);
+    }
 
-                let mut eval_args: HashMap<String, Value> = HashMap::new();
+    let variable = match slot {
+        Some(Slot { depth, index }) => enclosing.lock().unwrap().get_at(depth, index),
+        None => enclosing.lock().unwrap().get(&name),
+    };
 
-                for i in 0..arguments.len() {
-                    let arg = eval(arguments.get(i).unwrap(), enclosing.clone(), overloadings)?;
-                    let arg_name = needed_arguments.get(i).unwrap();
+    let mut value = match variable {
+        Some(variable) => variable,
+        None => return ThorLangError::eval_error(eq_token_index),
+    };
 
-                    eval_args.insert(arg_name.to_string(), arg);
-                }
+    //if we assign to a value that is nil we make it an object
+    //so we can make something like this:
+    //
+    //let obj;
+    //obj.hello = 4;
+    //^^^
+    //this makes obj an object
+    if value.value == ValueType::Nil {
+        value.value = ValueType::Object(Shared::new(HashMap::new()));
+    }
 
-                let function = get_registered_function(name)?;
+    //arrays and objects are shared, so walking down the fields only copies references to them and
+    //the value is put straight into the array or object that holds it
+    let mut current = value.clone();
 
-                let self_value = match self_value {
-                    Some(self_value) => Some(*self_value),
-                    None => None,
-                };
+    //runs for the first n - 1 items in the order list
+    for i in 1..(order.len() - 1) {
+        let (current_field_key, current_field_key_index) = &order[i];
 
-                return function(
-                    eval_args,
-                    self_value,
-                    Some(enclosing.clone()),
-                    var_name,
-                    env_state,
-                );
+        let next = match (&current.value, current_field_key) {
+            //in this case we have an array call (-Assignment)
+            (ValueType::Array(arr), FieldKey::Int(num)) => {
+                arr.lock().get_mut(*num as usize).map(become_object)
             }
+            //in this one a fieldcall (-Assignment)
+            (ValueType::Object(obj), FieldKey::String(str)) => {
+                obj.lock().get_mut(str).map(become_object)
+            }
+            _ => None,
+        };
 
-            if let ValueType::Function(Function::ThorFunction {
-                body,
-                needed_arguments,
-                closure,
-                name
-            }) = function.value
-            {
-                if needed_arguments.len() != arguments.len() {
-                    return ThorLangError::function_arity_error(
-                        paren_token_index.clone(),
-                        needed_arguments.len(),
-                        arguments.len(),
-                    );
-                }
-
-                // Evaluate the arguments in the current environment
-                let mut eval_args: HashMap<String, Value> = HashMap::new();
-                for i in 0..arguments.len() {
-                    let arg = eval(arguments.get(i).unwrap(), enclosing.clone(), overloadings)?;
-                    let arg_name = needed_arguments.get(i).unwrap();
-                    eval_args.insert(arg_name.to_string(), arg);
-                }
-
-                // Create a new environment for the function call, using the closure's environment
-                let function_env = Environment::new(Some(closure.clone())); // Only capture the closure's environment
+        current = match (next, current_field_key) {
+            (Some(next), _) => next,
+            (None, FieldKey::Int(num)) => {
+                return ThorLangError::index_error(*current_field_key_index, current, *num as f64)
+            }
+            (None, FieldKey::String(_)) => {
+                return ThorLangError::retrieval_error(*current_field_key_index)
+            }
+        };
+    }
 
-                for (name, value) in eval_args {
-                    function_env
-                        .lock()
-                        .unwrap()
-                        .values
-                        .lock().unwrap()
-                        .insert(name, value);
-                }
+    let (last_key, last_key_index) = &order[order.len() - 1];
 
-                //when the function is a thorfunction we need to eval the block (body of the
-                //function) with an environemnt that is copied from the current one but appended
-                //with the arguments
-                eval_statement(body, function_env, overloadings)
+    //runs for the last (nth) field in the order list
+    match (&current.value, last_key) {
+        (ValueType::Object(obj), FieldKey::String(key)) => {
+            obj.lock().insert(key.to_string(), eval_value.clone());
+        }
+        (ValueType::Array(arr), FieldKey::Int(num)) => {
+            if let Some(element) = arr.lock().get_mut(*num as usize) {
+                *element = eval_value.clone();
             } else {
-                return ThorLangError::unkown_function_error(paren_token_index.clone());
+                return ThorLangError::index_error(*last_key_index, current.clone(), *num as f64);
             }
         }
+        _ => return ThorLangError::eval_error(eq_token_index),
+    }
 
-        Expression::Assignment {
-            target,
-            value,
-            eq_token_index,
-        } => {
-            let eval_value = eval(value, enclosing.clone(), overloadings)?;
+    let set_val = set_variable(&enclosing, &name, slot, value.clone(), eq_token_index)?;
 
+    run_listeners(set_val, &enclosing, overloadings);
 
-            //Assignment needs to find the target first
+    return Ok(eval_value);
+}
+
+//nil fields that get fields assigned become objects as well, returns the (shared) field
+fn become_object(field: &mut Value) -> Value {
+    if field.value == ValueType::Nil {
+        field.value = ValueType::Object(Shared::new(HashMap::new()));
+    }
 
-            //iteratively go over the fields (creating them when they do not exist) and putting in
-            //the value at the deepest level
-            //
-            //iterating over order (a vector of keys, can be numbers for arrays or strings for
-            //objects)
-            let order = generate_field_order(target.clone(), enclosing.clone(), overloadings)?;
+    field.clone()
+}
 
-            let value: &mut Value = &mut enclosing
+fn set_variable(
+    enclosing: &Arc<Mutex<Environment>>,
+    name: &str,
+    slot: Option<Slot>,
+    value: Value,
+    eq_token_index: usize,
+) -> Result<Value, ThorLangError> {
+    return match slot {
+        Some(Slot { depth, index }) => {
+            enclosing
                 .lock()
                 .unwrap()
-                .get(&order.get(0).unwrap().0.get_string().unwrap().to_string())
-                .unwrap()
-                .clone();
-
-            if order.len() == 1 {
-                let set_val = enclosing.lock().unwrap().set(
-                    order.get(0).unwrap().0.get_string().unwrap(),
-                    eval_value.clone(),
-                    *eq_token_index,
-                )?;
-
-                if let Some(listeners) = set_val.listeners {
-                    for listener in listeners {
-                        let _ = eval_statement(listener, enclosing.clone(), overloadings);
-                    }
-                }
-
-                return Ok(eval_value);
-            }
-
-            let mut current: &mut Value = value;
-
-            //runs for the first n - 1 items in the order list
-            for i in 1..(order.len() - 1) {
-                //nil values that get fields reassigned become objects
-                let immut_value = current.clone();
-
-                let current_field_key = &order.get(i).unwrap().0;
-
-                let current_field_key_index = &order.get(i).unwrap().1;
-
-                //in this case we have an array call (-Assignment)
-                if let FieldKey::Int(num) = current_field_key {
-                    if let ValueType::Array(ref mut arr) = current.value {
-                        if let Some(current_mut) = arr.get_mut(*num as usize) {
-                            current = current_mut
-                        } else {
-                            return ThorLangError::index_error(
-                                current_field_key_index - 1,
-                                immut_value,
-                                *num as f64,
-                            );
-                        }
-                    }
-                }
-
-                //in this one a fieldcall (-Assignment)
-                if let FieldKey::String(ref str) = current_field_key {
-                    if let Some(field) = current.fields.get_mut(str) {
-                        current = field;
-                    } else {
-                        return ThorLangError::retrieval_error(current_field_key_index - 1);
-                    }
-                }
-            }
-
-            let last_key = order.get(order.len() - 1).unwrap();
-
-            //runs for the last (nth) field in the order list (or the first when we assing to a
-            //single variable)
-            match &last_key.0 {
-                FieldKey::String(key) => {
-                    current.fields.insert(key.to_string(), eval_value.clone());
-                }
-                FieldKey::Int(num) => {
-                    if let ValueType::Array(arr) = &mut current.value {
-                        if let Some(_value) = arr.get(*num as usize) {
-                            arr[*num as usize] = eval_value.clone();
-                        } else {
-                            return ThorLangError::index_error(
-                                last_key.1,
-                                current.clone(),
-                                *num as f64,
-                            );
-                        }
-                    }
-                }
-            }
-
-            //if we assign to a value that is nil we make it an object
-            //so we can make something like this:
-            //
-            //let obj;
-            //obj.hello = 4;
-            //^^^
-            //this makes obj an object
-            if current.value == ValueType::Nil {
-                current.value = ValueType::Object
-            }
-
-            let set_val = enclosing.lock().unwrap().set(
-                order.get(0).unwrap().0.get_string().unwrap().to_string(),
-                value.clone(),
-                *eq_token_index,
-            )?;
-
-            if let Some(listeners) = set_val.listeners {
-                for listener in listeners {
-                    let _ = eval_statement(listener, enclosing.clone(), overloadings);
-                }
-            }
+                .set_at(depth, index, value, eq_token_index)
+        }
+        None => enclosing
+            .lock()
+            .unwrap()
+            .set(name.to_string(), value, eq_token_index),
+    };
+}
 
-            return Ok(eval_value);
+//listeners are functions without arguments, errors inside of them are ignored
+fn run_listeners(
+    set_val: Value,
+    enclosing: &Arc<Mutex<Environment>>,
+    overloadings: &mut Overloadings,
+) {
+    if let Some(listeners) = set_val.listeners {
+        for listener in listeners {
+            let _ = eval_function(listener, vec![], enclosing.clone(), overloadings);
         }
+    }
+}
 
-        //kind of like literals, but will replace instantly with the value behind the variable name
-        //instead of going down to literals first
-        Expression::Identifier {
-            name,
-            identifier_token_index,
-        } => {
-            let value = enclosing.lock().unwrap().get(name);
+//expressions are compiled and run on the vm as well, this is used by natives that need to evaluate
+//a single expression
+pub fn eval(
+    expr: &Expression,
+    enclosing: Arc<Mutex<Environment>>,
+    overloadings: &mut Overloadings,
+) -> Result<Value, ThorLangError> {
+    let mut expr = expr.clone();
+    resolve_expression(&mut expr, &HashSet::new())?;
 
-            if let Some(val) = value {
-                return Ok(val);
-            } else {
-                return ThorLangError::unknown_value_error(*identifier_token_index);
-            }
-        }
+    let chunk = compile_expression(&expr)?;
 
-        //these just return the value they evaluate to
-        Expression::Unary {
-            operator,
-            right,
-            operator_token_index,
-        } => {
-            return eval_unary(
-                operator.clone(),
-                &right,
-                enclosing.clone(),
-                overloadings,
-                *operator_token_index,
-            )
-        }
-        Expression::Literal {
-            literal,
-            literal_token_index,
-        } => return eval_literal(literal.clone(), *literal_token_index),
-        Expression::Grouping { inner } => return eval(&inner, enclosing, overloadings),
-        Expression::Binary {
-            left,
-            operator,
-            right,
-            operator_token_index,
-        } => {
-            return eval_binary(
-                &left,
-                operator.clone(),
-                &right,
-                enclosing,
-                overloadings,
-                *operator_token_index,
-            )
-        }
-    }
+    run_chunk(Arc::new(chunk), enclosing, overloadings)
 }
 
 #[derive(Debug)]
-enum FieldKey {
+pub(crate) enum FieldKey {
     Int(i32),
     String(String),
 }
@@ -1150,107 +938,3 @@ impl FieldKey {
         };
     }
 }
-
-//generates the field Assignment order by traversing the field calls and returning the steps we
-//need to take
-//
-//obj.hello[0]["hello"] turns to ["hello", 0, "hello"]
-fn generate_field_order(
-    target: Box<Expression>,
-    enclosing: Arc<Mutex<Environment>>,
-    overloadings: &mut Overloadings,
-) -> Result<Vec<(FieldKey, usize)>, ThorLangError> {
-    let mut order = vec![];
-
-    let mut current = target;
-
-    let mut not_ended = true;
-
-    while not_ended {
-        match *current.clone() {
-            //when a single identifier we just return the name (of the variable)
-            Expression::Identifier {
-                name,
-                identifier_token_index,
-            } => {
-                order.push((FieldKey::String(name), identifier_token_index));
-                not_ended = false;
-            }
-
-            //if it is a fieldcall we return the key name
-            Expression::FieldCall {
-                callee,
-                key,
-                dot_token_index,
-            } => {
-                if let Expression::Identifier {
-                    name,
-                    identifier_token_index,
-                } = *key
-                {
-                    order.push((FieldKey::String(name), identifier_token_index.clone()));
-                } else {
-                    order.push((
-                        FieldKey::String(hash_value(eval(&key, enclosing.clone(), overloadings)?)),
-                        dot_token_index,
-                    ));
-                }
-
-                current = callee
-            }
-
-            //same in the case of a retrieve however this can also be an integer
-            Expression::Retrieve {
-                retrievee,
-                key,
-                lbrack_token_index,
-            } => {
-                let key = eval(&key, enclosing.clone(), overloadings)?;
-
-                match key.value {
-                    ValueType::String(str) => {
-                        let key = str;
-
-                        order.push((FieldKey::String(key), lbrack_token_index));
-                    }
-                    ValueType::Number(num) => {
-                        if num.round() != num.round() {
-                            let eval_value = eval(&retrievee, enclosing.clone(), overloadings)?;
-
-                            if let Err(err) =
-                                ThorLangError::index_error(lbrack_token_index, eval_value, num)
-                            {
-                                return Err(err);
-                            }
-                        }
-
-                        let index = num as i32;
-
-                        order.push((FieldKey::Int(index), lbrack_token_index));
-                    }
-                    _ => unimplemented!(),
-                }
-
-                current = retrievee;
-            }
-            _ => (),
-        }
-    }
-
-    order.reverse();
-    Ok(order)
-}
-
-//helper function used in dot with numbers around
-//this function could also be made with a "format!" call but this is some math wizardry and i like
-//it
-fn combine_f64(num1: f64, num2: f64) -> f64 {
-    // Determine the number of decimal places for num2
-    let decimal_places = 10f64.powi(num2.abs().log10().floor() as i32 + 1);
-
-    // Shift num2 to the right to match it as a fractional part
-    let fractional_part = num2 / decimal_places;
-
-    // Combine num1 with the fractional part of num2
-    num1 + fractional_part
-}
@@ -0,0 +1,283 @@
+use crate::{
+    compile_program, diagnose, lexer, parse, parse_recovering, register_array_methods,
+    register_native_functions, register_number_methods, register_string_methods, resolve,
+    run_chunk, Diagnostic,
+};
+
+use type_lib::*;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+//an interpreter owns a global environment and the overloadings defined in it, so code run with the
+//same instance can use everything earlier runs have defined (this is what the repl needs).
+//
+//unlike interpret_code errors are returned as a diagnostic and nothing is printed
+pub struct Interpreter {
+    env_state: EnvState,
+    //the name of the file the code comes from, errors and traces show it
+    file: Option<String>,
+    global_env: Arc<Mutex<Environment>>,
+    overloadings: Overloadings,
+    registry: RegisteredFnMap,
+}
+
+//builder for the interpreter
+#[derive(Default)]
+pub struct InterpreterBuilder {
+    env_state: Option<EnvState>,
+    file: Option<String>,
+    registry: Option<RegisteredFnMap>,
+    natives: Vec<(Value, FnType)>,
+    output: Option<OutputStream>,
+    input: Option<InputStream>,
+}
+
+impl InterpreterBuilder {
+    //the env_state is used by natives like import to resolve files
+    pub fn env_state(mut self, env_state: EnvState) -> Self {
+        self.env_state = Some(env_state);
+        self
+    }
+
+    //the file the code that is run comes from
+    pub fn file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_string());
+        self
+    }
+
+    //everything scripts print is written to this stream instead of stdout
+    pub fn output(mut self, output: OutputStream) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    //get_input reads from this stream instead of stdin
+    pub fn input(mut self, input: InputStream) -> Self {
+        self.input = Some(input);
+        self
+    }
+
+    //shares an existing registry instead of creating a new one, imported modules use this to get
+    //the same natives as the code that imported them
+    pub fn registry(mut self, registry: RegisteredFnMap) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    //adds a native function to the global environment, the value has to be a named function without
+    //a self value (for example Value::simple_function) and the body is registered under its name.
+    //natives added here replace built in natives with the same name, the methods of values (like
+    //len of arrays) are registered apart from them and can't be replaced. any other value is
+    //returned as an error
+    pub fn native(mut self, value: Value, body: FnType) -> Result<Self, ThorLangError> {
+        match &value.value {
+            ValueType::Function(Function::NamedFunction {
+                self_value: None, ..
+            }) => self.natives.push((value, body)),
+            _ => {
+                return Err(ThorLangError::RuntimeError {
+                    message: format!(
+                        "natives have to be named functions without a self value, not {}",
+                        stringify_value(value)
+                    ),
+                })
+            }
+        }
+        Ok(self)
+    }
+
+    //registers a closure of the host under the given name, it shows up in the global environment
+    //next to the built in natives. the closure gets the arguments in the order they were passed
+    //in and is only called with exactly "arity" arguments
+    pub fn function<F>(mut self, name: &str, arity: usize, function: F) -> Self
+    where
+        F: Fn(Vec<Value>) -> Result<Value, ThorLangError> + Send + Sync + 'static,
+    {
+        //named functions get their arguments by name, so the positions are turned into names
+        let needed_arguments: Vec<String> = (0..arity).map(|i| format!("#{i}")).collect();
+
+        let value = Value {
+            value: ValueType::Function(Function::NamedFunction {
+                name: name.to_string(),
+                needed_arguments: needed_arguments.clone(),
+                self_value: None,
+                env_state: None,
+            }),
+            ..Value::default()
+        };
+
+        let body: FnType = Arc::new(move |mut args, _, _, _| {
+            let arguments = needed_arguments
+                .iter()
+                .map(|key| args.remove(key).unwrap_or_default())
+                .collect();
+
+            function(arguments)
+        });
+
+        //always a named function, so it doesn't need the checks of native
+        self.natives.push((value, body));
+        self
+    }
+
+    pub fn build(self) -> Interpreter {
+        let mut env_state = self.env_state.unwrap_or_default();
+
+        if let Some(output) = self.output {
+            env_state.output = Some(output);
+        }
+        if let Some(input) = self.input {
+            env_state.input = Some(input);
+        }
+
+        //a shared registry already has the built in natives (and the natives of the embedder that
+        //might replace them), registering them again would undo that
+        let registry = match self.registry {
+            Some(registry) => registry,
+            None => {
+                let registry = RegisteredFnMap::default();
+
+                //the methods of primitive values are registered once, field accesses only look
+                //them up
+                register_string_methods(&registry);
+                register_number_methods(&registry);
+                register_array_methods(&registry);
+
+                let natives = register_native_functions(env_state.clone(), &registry);
+                registry.lock().unwrap().globals.extend(natives);
+
+                registry
+            }
+        };
+
+        //the natives of the embedder are registered after the built in ones and replace them
+        for (value, body) in self.natives {
+            if let ValueType::Function(Function::NamedFunction { name, .. }) = &value.value {
+                value.register_function_body(&registry, body);
+                registry
+                    .lock()
+                    .unwrap()
+                    .globals
+                    .insert(name.to_string(), value.clone());
+            }
+        }
+
+        //the global env instantiation (global values and functions)
+        let natives: HashMap<String, Value> = registry.lock().unwrap().globals.clone();
+
+        let global_env = Arc::new(Mutex::new(Environment {
+            values: Arc::new(Mutex::new(natives)),
+            slots: vec![],
+            names: vec![],
+            enclosing: None,
+            overloadings: HashMap::new(),
+            registry: Some(registry.clone()),
+            env_state: Some(env_state.clone()),
+        }));
+
+        Interpreter {
+            env_state,
+            file: self.file,
+            global_env,
+            overloadings: HashMap::new(),
+            registry,
+        }
+    }
+}
+
+impl Interpreter {
+    pub fn builder() -> InterpreterBuilder {
+        InterpreterBuilder::default()
+    }
+
+    pub fn env_state(&self) -> &EnvState {
+        &self.env_state
+    }
+
+    //the native functions of this interpreter, no other interpreter uses it (unless it is passed to
+    //InterpreterBuilder::registry)
+    pub fn registry(&self) -> RegisteredFnMap {
+        self.registry.clone()
+    }
+
+    //the environment all top level variables of every run are stored in
+    pub fn global_env(&self) -> Arc<Mutex<Environment>> {
+        self.global_env.clone()
+    }
+
+    //every syntax error of the code (or its lexer error), the code is not run. run only returns the
+    //first error, this is for showing all of them at once
+    pub fn check(text: &str) -> Vec<Diagnostic> {
+        let tokens = match lexer(text.to_string()) {
+            Ok(tokens) => tokens,
+            Err(error) => return vec![diagnose(text, &[], error)],
+        };
+
+        let (_, errors) = parse_recovering(tokens.clone());
+
+        return errors
+            .into_iter()
+            .map(|error| diagnose(text, &tokens, error))
+            .collect();
+    }
+
+    //runs the given code and returns either the value of a top level return statement, or the
+    //value of the last statement when it is an expression, or nil
+    pub fn run(&mut self, text: &str) -> Result<Value, Diagnostic> {
+        //the error knows the code it happened in, so no tokens need to be passed
+        return self
+            .evaluate(text)
+            .map_err(|error| diagnose(text, &[], error));
+    }
+
+    //runs the code like run, but returns the error itself instead of a diagnostic. errors are
+    //always traced with the source they happened in, imported modules hand their errors to the
+    //importing code like this
+    pub fn evaluate(&mut self, text: &str) -> Result<Value, ThorLangError> {
+        let mut source = Source {
+            name: self.file.clone(),
+            text: text.to_string(),
+            tokens: vec![],
+        };
+
+        //lexer errors point at their line and column themselves, they don't need any tokens
+        source.tokens = match lexer(text.to_string()) {
+            Ok(tokens) => tokens,
+            Err(error) => return Err(error.traced(Some(Arc::new(source)), vec![])),
+        };
+
+        let source = Arc::new(source);
+
+        return self
+            .run_source(source.clone())
+            .map_err(|error| error.traced(Some(source), vec![]));
+    }
+
+    fn run_source(&mut self, source: Arc<Source>) -> Result<Value, ThorLangError> {
+        let mut ast = parse(source.tokens.clone())?;
+
+        //globals of earlier runs (and the natives) can be used before anything is declared
+        let known_globals: HashSet<String> = self
+            .global_env
+            .lock()
+            .unwrap()
+            .values
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+
+        resolve(&mut ast, &known_globals)?;
+
+        //if the last statement is an expression the program returns its value
+        let chunk = compile_program(&ast, Some(source))?;
+
+        run_chunk(
+            Arc::new(chunk),
+            self.global_env.clone(),
+            &mut self.overloadings,
+        )
+    }
+}
@@ -1,401 +1,424 @@
-use regex::Regex;
+use type_lib::{ThorLangError, Token, TokenType};
+
+//the lexer walks over the text exactly once, char by char. every token remembers where it starts
+//(as a byte offset into the text and as line and column) and how many bytes it spans, since the
+//text is not split into lines tokens can go over multiple lines (strings for example)
+struct Lexer<'a> {
+    text: &'a str,
+    tokens: Vec<Token>,
+    //byte offset of the next character
+    offset: usize,
+    //line and column of the next character, both start at 1
+    line: i32,
+    column: i32,
+}
 
-use type_lib::{Token, TokenType};
+//where a token started, taken before its first character is consumed
+#[derive(Clone, Copy)]
+struct Start {
+    offset: usize,
+    line: i32,
+    column: i32,
+}
 
-//just a helper function for instantiation
-//could be rewritten as an impl, but would make the code even more verbose so no
-fn simple_token(token_type: TokenType, line: i32, column: i32) -> Token {
-    return Token {
-        token_type,
-        line,
-        column,
+//keywords are lexed like identifiers and then turned into their own token type
+fn keyword(identifier: &str) -> Option<TokenType> {
+    let token_type = match identifier {
+        "try" => TokenType::TRY,
+        "match" => TokenType::MATCH,
+        "to" => TokenType::TO,
+        "step" => TokenType::STEP,
+        "on" => TokenType::ON,
+
+        "overload" => TokenType::OVERLOAD,
+        "if" => TokenType::IF,
+        "else" => TokenType::ELSE,
+        "true" => TokenType::TRUE,
+        "false" => TokenType::FALSE,
+        "while" => TokenType::WHILE,
+        "for" => TokenType::FOR,
+        "in" => TokenType::IN,
+        "fn" => TokenType::FN,
+        "nil" => TokenType::NIL,
+        "let" => TokenType::LET,
+        "print" => TokenType::PRINT,
+        "do" => TokenType::DO,
+        "return" => TokenType::RETURN,
+        "throw" => TokenType::THROW,
+        "break" => TokenType::BREAK,
+        "continue" => TokenType::CONTINUE,
+        "and" => TokenType::AND,
+        "or" => TokenType::OR,
+        _ => return None,
     };
-}
 
-//returns the next character in the text
-fn peek(current_index: usize, text: &str) -> String {
-    //returns char at iter + 1
+    return Some(token_type);
+}
 
-    return text.chars().nth(current_index + 1).unwrap().to_string();
+//identifiers have the following regular form : [a-zA-Z_]([a-zA-Z0-9_])*
+fn is_identifier_start(char: char) -> bool {
+    return char == '_' || char.is_ascii_alphabetic();
 }
 
-//just a wrapper type, unelegant and will be overworked in the future
-//the token is just returned, while "iter_skip_steps" helps the loop keep track of how many chars
-//can safely be skipped because they have already been handled in one of the helper functions
-struct OuterIter {
-    token: Token,
-    iter_skip_steps: usize,
+fn is_identifier_char(char: char) -> bool {
+    return char == '_' || char.is_ascii_alphanumeric();
 }
 
-fn iterate_string(current_index: usize, text: &str, current_line: i32, column: i32) -> OuterIter {
-    //iterate string skips at least one (the first matching) character
-    let mut iter_skip_steps: usize = 1;
+impl Lexer<'_> {
+    //the next character (without consuming it)
+    fn peek(&self) -> Option<char> {
+        return self.text[self.offset..].chars().next();
+    }
+
+    //the character after the next one
+    fn peek_next(&self) -> Option<char> {
+        return self.text[self.offset..].chars().nth(1);
+    }
 
-    let mut string = "".to_string();
+    //consumes the next character and moves the line and column along
+    fn advance(&mut self) -> Option<char> {
+        let char = self.peek()?;
 
-    while let Some(char) = text.chars().nth(current_index + iter_skip_steps) {
-        let char = char.to_string();
+        self.offset += char.len_utf8();
 
-        //while there is some character that is not a " the string goes on and on
-        if char != "\"" {
-            string += &char;
-            iter_skip_steps += 1;
+        if char == '\n' {
+            self.line += 1;
+            self.column = 1;
         } else {
-            iter_skip_steps += 1;
-            break;
+            self.column += 1;
         }
+
+        return Some(char);
     }
 
-    return OuterIter {
-        token: Token {
-            token_type: TokenType::STRING(string),
-            line: current_line,
-            column,
-        },
-        iter_skip_steps,
-    };
-}
+    //consumes the next character only if it is the expected one
+    fn advance_if(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            return true;
+        }
+
+        return false;
+    }
 
-fn iterate_number(current_index: usize, text: &str, current_line: i32, column: i32) -> OuterIter {
-    //same as before
-    let mut iter_skip_steps = 0;
-    let mut number = "".to_string();
-    let number_regex = Regex::new(r"[0-9]").unwrap();
-    let mut dot_used = false;
+    fn start(&self) -> Start {
+        return Start {
+            offset: self.offset,
+            line: self.line,
+            column: self.column,
+        };
+    }
 
-    while let Some(char) = text.chars().nth(current_index + iter_skip_steps) {
-        let char = char.to_string();
+    //pushes a token that goes from start up to the current character
+    fn push(&mut self, token_type: TokenType, start: Start) {
+        self.tokens.push(Token {
+            token_type,
+            line: start.line,
+            column: start.column,
+            offset: start.offset,
+            length: self.offset - start.offset,
+        });
+    }
 
-        //quite simple if the current char is a number, mark it down
-        if number_regex.is_match(&char) {
-            number += &char;
-            iter_skip_steps += 1;
+    //strings go until the next " and can span multiple lines. strings that start with """ only end
+    //at the next """, so they can contain single quotes without escaping them. a newline directly
+    //after the opening """ is not part of the string
+    //
+    //every ${ in a string ends the current part (as a STRINGPART token), then the tokens of the
+    //expression up to the closing } follow and the string goes on after it
+    fn string(&mut self, start: Start) -> Result<(), ThorLangError> {
+        let triple = self.peek() == Some('"') && self.peek_next() == Some('"');
+
+        if triple {
+            self.advance();
+            self.advance();
+            self.advance_if('\n');
         }
-        //if we encounter a dot and we have already used one we cannot mark the dot down
-        else if (char == ".".to_string()) && !dot_used {
-            //else we need to check if the next char even is a number else we cannot mark the dot
-            //as part of the number and it is a grouping operator
-            let next_char = peek(current_index + iter_skip_steps, text);
-            dot_used = true;
-            if number_regex.is_match(&next_char) {
-                number += &char;
 
-                //this is the part that consumes the dot token in case the next char is a number
-                iter_skip_steps += 1;
-            } else {
-                break;
+        let mut string = String::new();
+        let mut part_start = start;
+
+        loop {
+            let escape_start = self.start();
+
+            let Some(char) = self.advance() else {
+                return Err(ThorLangError::UnterminatedString {
+                    line: start.line,
+                    column: start.column,
+                });
+            };
+
+            match char {
+                '"' if !triple => break,
+                '"' if self.text[self.offset..].starts_with("\"\"") => {
+                    self.advance();
+                    self.advance();
+                    break;
+                }
+                '\\' => string.push(self.escape(escape_start)?),
+                '$' if self.advance_if('{') => {
+                    self.push(
+                        TokenType::STRINGPART(std::mem::take(&mut string)),
+                        part_start,
+                    );
+                    self.interpolated_expression(start)?;
+
+                    part_start = self.start();
+                }
+                _ => string.push(char),
             }
-        } else {
-            break;
         }
-    }
 
-    //return the currently accumulated number
-    return OuterIter {
-        token: Token {
-            token_type: TokenType::NUMBER(number),
-            line: current_line,
-            column,
-        },
-        iter_skip_steps,
-    };
-}
+        self.push(TokenType::STRING(string), part_start);
 
-fn iterate_identifier(
-    current_index: usize,
-    text: &str,
-    current_line: i32,
-    column: i32,
-) -> OuterIter {
-    //identifiers have the following regular form : [a-zA-Z]([a-zA-Z0-9]|_)*
-    //this method only has to check from the second letter onwards (till the end of the "word").
+        return Ok(());
+    }
 
-    let id_regex = Regex::new(r"[a-zA-Z0-9]|_").unwrap();
-    let mut iter_skip_steps: usize = 0; //counts how many iterations have to be skipped in the main iteration loop
+    //lexes the tokens of the expression inside of a ${...} up to (and including) the closing brace
+    //(an INTERPOLATIONEND token), braces of objects or blocks inside of the expression are counted
+    //so they don't end it
+    fn interpolated_expression(&mut self, string_start: Start) -> Result<(), ThorLangError> {
+        let mut depth = 0;
+
+        loop {
+            let token_start = self.start();
+
+            match self.peek() {
+                None => {
+                    return Err(ThorLangError::UnterminatedString {
+                        line: string_start.line,
+                        column: string_start.column,
+                    })
+                }
+                Some('}') if depth == 0 => {
+                    self.advance();
+                    self.push(TokenType::INTERPOLATIONEND, token_start);
+                    return Ok(());
+                }
+                _ => (),
+            }
 
-    let mut identifier = "".to_string();
+            let first_token = self.tokens.len();
 
-    //iterate over the identifier until there are no more letters that match the identifier_regex
-    while let Some(char) = text.chars().nth(current_index + iter_skip_steps) {
-        let char = char.to_string();
+            self.token()?;
 
-        if id_regex.is_match(&char) {
-            //again we need to tell the loop how many iterations are safely to skip
-            iter_skip_steps += 1;
-            identifier += &char;
-        } else {
-            break;
+            match self.tokens.get(first_token).map(|token| &token.token_type) {
+                Some(TokenType::LBRACE) => depth += 1,
+                Some(TokenType::RBRACE) => depth -= 1,
+                _ => (),
+            }
         }
     }
-    //makes default token_type usually this will be returned
-    let mut token_type = TokenType::IDENTIFIER(identifier.clone());
-    //check if identifier is part of keywords and if it is change the tokentype
-    match identifier.as_str() {
-        "try" => token_type = TokenType::TRY,
-        "to" => token_type = TokenType::TO,
-        "step" => token_type = TokenType::STEP,
-        "on" => token_type = TokenType::ON,
-
-        "overload" => token_type = TokenType::OVERLOAD,
-        "if" => token_type = TokenType::IF,
-        "else" => token_type = TokenType::ELSE,
-        "true" => token_type = TokenType::TRUE,
-        "false" => token_type = TokenType::FALSE,
-        "while" => token_type = TokenType::WHILE,
-        "for" => token_type = TokenType::FOR,
-        "in" => token_type = TokenType::IN,
-        "fn" => token_type = TokenType::FN,
-        "nil" => token_type = TokenType::NIL,
-        "let" => token_type = TokenType::LET,
-        "print" => token_type = TokenType::PRINT,
-        "do" => token_type = TokenType::DO,
-        "return" => token_type = TokenType::RETURN,
-        "throw" => token_type = TokenType::THROW,
-        _ => (),
-    }
 
-    return OuterIter {
-        token: Token {
-            token_type,
-            line: current_line,
-            column,
-        },
-        iter_skip_steps,
-    };
-}
-
-fn iterate_comment(current_index: usize, text: &str) -> usize {
-    let mut iter_skip_steps = 0;
+    //the character an escape sequence stands for, the backslash is already consumed. errors point
+    //at the backslash
+    fn escape(&mut self, start: Start) -> Result<char, ThorLangError> {
+        let invalid = |lexer: &Self| ThorLangError::InvalidEscape {
+            escape: lexer.text[start.offset..lexer.offset].to_string(),
+            line: start.line,
+            column: start.column,
+        };
+
+        let char = match self.advance() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('$') => '$',
+            //unicode escapes look like \u{1F600}, with one to six hex digits
+            Some('u') => {
+                if !self.advance_if('{') {
+                    return Err(invalid(self));
+                }
 
-    //iterates until it finds newline character (here 0xA) and then returns the number of
-    //iter_skip_steps
-    //i.e counts the chars in the comment
-    while let Some(char) = text.chars().nth(current_index + iter_skip_steps) {
-        if char == 0xA as char {
-            return iter_skip_steps;
-        }
+                let digits_start = self.offset;
 
-        iter_skip_steps += 1;
-    }
-
-    return iter_skip_steps;
-}
+                while self.peek().is_some_and(|char| char.is_ascii_hexdigit()) {
+                    self.advance();
+                }
 
-pub fn line_column_lexer(text: String) -> Vec<Token> {
-    let mut tokens: Vec<Token> = vec![];
+                let digits = &self.text[digits_start..self.offset];
 
-    //regex to see whether a char is start of a number or identifier
-    //
-    //
-    //the identifier one matches everything that is not a reserved character;
-    let identifier_start_regex = Regex::new(r"_|[a-zA-Z]").unwrap();
-    let number_start_regex = Regex::new(r"[0-9]").unwrap();
-
-    //the lines of code
-    let lines = text.split("\n");
-
-    //instead of like before this code loops over every line instead of every character,
-    //the time complexety stays the same, but line and column handling
-    //and thus later error handling become easier to accomplish
-    for (line_count, line) in lines.clone().enumerate() {
-        //this is the number that appears in your error later
-        //we add one because iterator usually start at 0 but line 0 is a bit dumb...
-        let line_count = line_count as i32 + 1;
-
-        //the amount of characters a given "word" has is also the amount of characters we have to
-        //skip
-        let mut skip_chars = 0;
-        for (column, char) in line.chars().enumerate() {
-            //same for columns, theyll come in handy if we want to make cool and useful error
-            //messages later on
-            let column_count = column as i32 + 1;
-
-            //if we want to skip character in an iteration we just increase skip_chars
-            if skip_chars > 0 {
-                skip_chars -= 1;
-                continue;
-            }
+                if !self.advance_if('}') || digits.is_empty() || digits.len() > 6 {
+                    return Err(invalid(self));
+                }
 
-            if char.is_whitespace() {
-                continue;
+                u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid(self))?
             }
-            //convert char into string for easier comparison
-            let char = char.to_string();
-
-            //these below couldnt fit into a match because they are dynamic and also kind of
-            //different
-            //if we match one of these characters they all go into a seperate mode and look for the
-            //ending character and if we encounter it we return the accumulated string value and
-            //how much characters it has (we have to skip)
-            if char == "\"" {
-                let token_iter = iterate_string(column, line, line_count, column_count);
-
-                tokens.push(token_iter.token);
-                //these methods overshoot by one (because of the looping behaviour) so they have to
-                //be put one behind
-                skip_chars = token_iter.iter_skip_steps - 1;
-                continue;
+            Some(_) => return Err(invalid(self)),
+            None => {
+                return Err(ThorLangError::UnterminatedString {
+                    line: start.line,
+                    column: start.column,
+                })
             }
+        };
 
-            if identifier_start_regex.is_match(&char) {
-                let token_iter = iterate_identifier(column, line, line_count, column_count);
+        return Ok(char);
+    }
+
+    fn number(&mut self, start: Start) {
+        let mut dot_used = false;
 
-                tokens.push(token_iter.token);
-                skip_chars = token_iter.iter_skip_steps - 1;
+        while let Some(char) = self.peek() {
+            if char.is_ascii_digit() {
+                self.advance();
                 continue;
             }
 
-            if number_start_regex.is_match(&char) {
-                let token_iter = iterate_number(column, line, line_count, column_count);
+            //a dot is only part of the number if a digit follows it (and there was no dot before),
+            //else it is a dot token (for example in 1.sqrt())
+            let is_decimal_dot = char == '.'
+                && !dot_used
+                && self.peek_next().is_some_and(|next| next.is_ascii_digit());
 
-                tokens.push(token_iter.token);
-                skip_chars = token_iter.iter_skip_steps - 1;
-                continue;
+            if !is_decimal_dot {
+                break;
             }
 
-            //matcht the token and return its corresponding value to
-            match char.as_str() {
-                "(" => {
-                    tokens.push(simple_token(TokenType::LPAREN, line_count, column_count));
-                    continue;
-                }
-                ")" => {
-                    tokens.push(simple_token(TokenType::RPAREN, line_count, column_count));
-                    continue;
-                }
-                "{" => {
-                    tokens.push(simple_token(TokenType::LBRACE, line_count, column_count));
-                    continue;
-                }
-                "}" => {
-                    tokens.push(simple_token(TokenType::RBRACE, line_count, column_count));
-                    continue;
-                }
-                "[" => {
-                    tokens.push(simple_token(TokenType::LBRACK, line_count, column_count));
-                    continue;
-                }
-                "]" => {
-                    tokens.push(simple_token(TokenType::RBRACK, line_count, column_count));
-                    continue;
-                }
-                ":" => {
-                    tokens.push(simple_token(TokenType::COLON, line_count, column_count));
-                    continue;
-                }
-                ";" => {
-                    tokens.push(simple_token(TokenType::SEMICOLON, line_count, column_count));
-                    continue;
-                }
-                "," => {
-                    tokens.push(simple_token(TokenType::COMMA, line_count, column_count));
-                    continue;
-                }
-                "." => {
-                    tokens.push(simple_token(TokenType::DOT, line_count, column_count));
-                    continue;
-                }
-                "*" => {
-                    tokens.push(simple_token(TokenType::STAR, line_count, column_count));
-                    continue;
-                }
-                "+" => {
-                    tokens.push(simple_token(TokenType::PLUS, line_count, column_count));
-                    continue;
-                }
-                "-" => {
-                    tokens.push(simple_token(TokenType::MINUS, line_count, column_count));
-                    continue;
-                }
+            dot_used = true;
+            self.advance();
+        }
 
-                //any of the below matches either a single or double character token depending of the
-                //next value, also if the next token also matches we need to skip to chars, if not we
-                //just move on marking down the single char token
-                "!" => {
-                    tokens.push(simple_token(
-                        if peek(column, line).as_str() == "=" {
-                            skip_chars = 2;
-                            TokenType::BANGEQ
-                        } else {
-                            TokenType::BANG
-                        },
-                        line_count,
-                        column_count,
-                    ));
-                    continue;
-                }
-                "=" => {
-                    tokens.push(simple_token(
-                        if peek(column, line).as_str() == "=" {
-                            skip_chars = 2;
-                            TokenType::EQEQ
-                        } else {
-                            TokenType::EQ
-                        },
-                        line_count,
-                        column_count,
-                    ));
-                    continue;
-                }
-                "<" => {
-                    tokens.push(simple_token(
-                        if peek(column, line).as_str() == "=" {
-                            skip_chars = 2;
-                            TokenType::LESSEQ
-                        } else {
-                            TokenType::LESS
-                        },
-                        line_count,
-                        column_count,
-                    ));
-                    continue;
-                }
-                ">" => {
-                    tokens.push(simple_token(
-                        if peek(column, line).as_str() == "=" {
-                            skip_chars = 2;
-                            TokenType::GREATEREQ
-                        } else {
-                            TokenType::GREATER
-                        },
-                        line_count,
-                        column_count,
-                    ));
-                    continue;
-                }
+        let number = self.text[start.offset..self.offset].to_string();
 
-                "/" => {
-                    if peek(column, line) == "/" {
-                        skip_chars = iterate_comment(column, line);
-                        continue;
-                    //in case of comment skips rest of line
-                    } else {
-                        tokens.push(simple_token(TokenType::SLASH, line_count, column_count));
-                        continue;
-                    }
-                }
+        self.push(TokenType::NUMBER(number), start);
+    }
+
+    fn identifier(&mut self, start: Start) {
+        while self.peek().is_some_and(is_identifier_char) {
+            self.advance();
+        }
+
+        let identifier = &self.text[start.offset..self.offset];
+
+        let token_type =
+            keyword(identifier).unwrap_or_else(|| TokenType::IDENTIFIER(identifier.to_string()));
+
+        self.push(token_type, start);
+    }
 
-                _ => {
-                    //special characters will always (like + - or similar) only consume a single
-                    //character to make operator chaining easier
-                    tokens.push(simple_token(
-                        TokenType::SPECIAL(char),
-                        line_count,
-                        column_count,
-                    ));
+    //comments go until the end of the line, the newline itself is left for the main loop
+    fn comment(&mut self) {
+        while self.peek().is_some_and(|char| char != '\n') {
+            self.advance();
+        }
+    }
+
+    //any of these is either a single or double character token, depending on whether an = follows
+    fn with_eq(&mut self, single: TokenType, double: TokenType) -> TokenType {
+        if self.advance_if('=') {
+            return double;
+        }
+
+        return single;
+    }
+
+    fn token(&mut self) -> Result<(), ThorLangError> {
+        let start = self.start();
+
+        let Some(char) = self.advance() else {
+            return Ok(());
+        };
+
+        if char.is_whitespace() {
+            return Ok(());
+        }
+
+        if char == '"' {
+            return self.string(start);
+        }
+
+        if is_identifier_start(char) {
+            self.identifier(start);
+            return Ok(());
+        }
+
+        if char.is_ascii_digit() {
+            self.number(start);
+            return Ok(());
+        }
+
+        let token_type = match char {
+            '(' => TokenType::LPAREN,
+            ')' => TokenType::RPAREN,
+            '{' => TokenType::LBRACE,
+            '}' => TokenType::RBRACE,
+            '[' => TokenType::LBRACK,
+            ']' => TokenType::RBRACK,
+            ':' => TokenType::COLON,
+            ';' => TokenType::SEMICOLON,
+            ',' => TokenType::COMMA,
+            '.' => TokenType::DOT,
+            '*' if self.advance_if('*') => TokenType::STARSTAR,
+            '*' => self.with_eq(TokenType::STAR, TokenType::STAREQ),
+            '%' => self.with_eq(TokenType::PERCENT, TokenType::PERCENTEQ),
+            '~' if self.advance_if('/') => TokenType::TILDESLASH,
+            '+' => self.with_eq(TokenType::PLUS, TokenType::PLUSEQ),
+            '-' => self.with_eq(TokenType::MINUS, TokenType::MINUSEQ),
+
+            //&& and || are the same as and / or, single & and | stay special characters
+            '&' if self.advance_if('&') => TokenType::AND,
+            '|' if self.advance_if('|') => TokenType::OR,
+
+            '!' => self.with_eq(TokenType::BANG, TokenType::BANGEQ),
+            '=' if self.advance_if('>') => TokenType::FATARROW,
+            '=' => self.with_eq(TokenType::EQ, TokenType::EQEQ),
+            '<' => self.with_eq(TokenType::LESS, TokenType::LESSEQ),
+            '>' => self.with_eq(TokenType::GREATER, TokenType::GREATEREQ),
+
+            '/' => {
+                //in case of a comment the rest of the line is skipped
+                if self.advance_if('/') {
+                    self.comment();
+                    return Ok(());
                 }
+
+                self.with_eq(TokenType::SLASH, TokenType::SLASHEQ)
             }
-        }
+
+            //special characters will always (like + - or similar) only consume a single
+            //character to make operator chaining easier
+            _ => TokenType::SPECIAL(char.to_string()),
+        };
+
+        self.push(token_type, start);
+
+        return Ok(());
+    }
+}
+
+pub fn line_column_lexer(text: String) -> Result<Vec<Token>, ThorLangError> {
+    let mut lexer = Lexer {
+        text: &text,
+        tokens: vec![],
+        offset: 0,
+        line: 1,
+        column: 1,
+    };
+
+    while lexer.offset < text.len() {
+        lexer.token()?;
     }
 
-    let lines: Vec<&str> = lines.collect();
+    //the eof token sits on the last line, behind everything else
+    let mut tokens = lexer.tokens;
 
-    tokens.push(simple_token(TokenType::EOF, lines.len() as i32, 0));
+    tokens.push(Token {
+        token_type: TokenType::EOF,
+        line: text.split('\n').count() as i32,
+        column: 0,
+        offset: text.len(),
+        length: 0,
+    });
 
-    tokens
+    Ok(tokens)
 }
 
 //puts it all together
-pub fn lexer(text: String) -> Vec<Token> {
-    line_column_lexer(text.clone())
+pub fn lexer(text: String) -> Result<Vec<Token>, ThorLangError> {
+    line_column_lexer(text)
 }
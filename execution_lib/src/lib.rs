@@ -1,63 +1,38 @@
+mod compiler;
 mod error;
 mod eval;
+mod interpreter;
 mod lexer;
 mod native_functions;
 mod parser;
+mod resolver;
+mod vm;
 
+pub use compiler::*;
 pub use error::*;
 pub use eval::*;
+pub use interpreter::*;
 pub use lexer::*;
 pub use native_functions::*;
 pub use parser::*;
+pub use resolver::*;
+pub use vm::*;
 
 use type_lib::*;
 
-use std::collections::HashMap;
-use std::panic;
-use std::sync::{Arc, Mutex};
-
 //allows functions files to return values that can be used by other files
 //basically modules
+//
+//this prints the error and panics when the code fails, embedders should use the Interpreter
+//instead which returns the error as a diagnostic
 pub fn interpret_code(text: String, env: EnvState) -> Value {
-    let tokens = lexer(text.clone());
-    //println!("{:#?}", tokens.clone());
-    panic::set_hook(Box::new(|x| {
-
-        //println!("{x}");
-    }));
-
-    //custom error handling can be defined in these match arms
-    let ast = match parse(tokens.clone()) {
-        Ok(stmts) => stmts,
-        Err(err) => {
-            handle_error(text, tokens.clone(), err);
-            panic!();
-        }
-    };
-
-    //println!("{:#?}", ast);
-
-    //the global env instantiation (global values and functions)
-    let natives: HashMap<String, Value> = register_native_functions(env);
-    let global_env = Arc::new(Mutex::new(Environment {
-        values: Arc::new(Mutex::new(natives)),
-        enclosing: None,
-        overloadings : HashMap::new()
-    }));
-
-    //same with overloadings
-    let overloadings = &mut HashMap::new();
+    let mut interpreter = Interpreter::builder().env_state(env).build();
 
-    //again try to run the given list of statements generated form "parse" and if they fail handle
-    //the error
-    //
-    //we need to return this because the import function and later maybe an "eval" function need to
-    //evaluate code from text inside of the runtime
-    return match eval_statement(ast, global_env, overloadings) {
-        Ok(val) => val,
-        Err(err) => {
-            handle_error(text, tokens, err);
-            panic!();
+    match interpreter.run(&text) {
+        Ok(value) => value,
+        Err(diagnostic) => {
+            println!("\n{diagnostic}\n");
+            panic!("{}", diagnostic.message);
         }
-    };
+    }
 }
@@ -1,25 +1,20 @@
-use crate::{interpret_code, EnvState, Environment, ThorLangError, Value, lexer, parse, eval_statement};
+use crate::{Interpreter, EnvState, Environment, ThorLangError, Value, lexer, parse, eval_statement};
 use libloading::{Library, Symbol};
 use type_lib::*;
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::UNIX_EPOCH;
 
-use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs;
-use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
-use ::std::io::{self, BufRead};
-
-static FN_MAP: RegisteredFnMap = Mutex::new(None);
 
 //loads a .so library and makes all the lib functions executable by storing the library in cache
 //using an Arc
 fn load_lib(path: String) -> Result<HashMap<String, Value>, ThorLangError> {
     unsafe {
         //load the lib
-        let lib = Library::new(path);
+        let lib = Library::new(&path);
 
         //check if the lib exists
         match lib {
@@ -44,16 +39,14 @@ fn load_lib(path: String) -> Result<HashMap<String, Value>, ThorLangError> {
 
                         Ok(ret_map)
                     }
-                    Err(e) => {
-                        println!("{:?}", e);
-                        Err(ThorLangError::UnknownError)
-                    }
+                    Err(e) => Err(ThorLangError::RuntimeError {
+                        message: format!("the library {path} has no value_map function: {e}"),
+                    }),
                 }
             }
-            Err(e) => {
-                println!("{:?}", e);
-                Err(ThorLangError::UnknownError)
-            }
+            Err(e) => Err(ThorLangError::RuntimeError {
+                message: format!("the library {path} can't be loaded: {e}"),
+            }),
         }
     }
 }
@@ -94,10 +87,9 @@ pub fn execute_lib_function(
                 >>(bytes)
                 {
                     Ok(function) => Ok(function),
-                    Err(e) => {
-                        println!("{:?}", e);
-                        Err(ThorLangError::UnknownError)
-                    }
+                    Err(e) => Err(ThorLangError::RuntimeError {
+                        message: format!("the library has no function {name_string}: {e}"),
+                    }),
                 }?;
 
 
@@ -112,10 +104,9 @@ pub fn execute_lib_function(
             let function =
                 match lib.get::<Symbol<extern "Rust" fn(HashMap<String, Value>) -> Value>>(bytes) {
                     Ok(function) => Ok(function),
-                    Err(e) => {
-                        println!("{:?}", e);
-                        Err(ThorLangError::UnknownError)
-                    }
+                    Err(e) => Err(ThorLangError::RuntimeError {
+                        message: format!("the library has no function {name_string}: {e}"),
+                    }),
                 }?;
 
 
@@ -130,51 +121,77 @@ pub fn execute_lib_function(
     Err(ThorLangError::UnknownError)
 }
 
-pub fn get_registered_function(name: String) -> Result<FnType, ThorLangError> {
-    let mut map = FN_MAP.lock().unwrap();
-
-    if let Some(ref mut map) = *map {
-        return Ok(map.get(&name).unwrap().clone());
+//looks up the body of a named function in the registry of the interpreter the environment belongs to,
+//functions with a self value are methods and are looked up by the type of the value
+pub fn get_registered_function(
+    enclosing: &Arc<Mutex<Environment>>,
+    name: String,
+    self_value: Option<&Value>,
+) -> Result<FnType, ThorLangError> {
+    let registry = enclosing.lock().unwrap().get_registry();
+
+    if let Some(registry) = registry {
+        let registry = registry.lock().unwrap();
+
+        let function = match self_value {
+            Some(self_value) => registry
+                .methods
+                .get(&NativeRegistry::method_key(self_value.type_name(), &name))
+                .map(|method| method.body.clone()),
+            None => registry.functions.get(&name).cloned(),
+        };
+
+        if let Some(function) = function {
+            return Ok(function);
+        }
     }
 
-    Err(ThorLangError::UnknownError)
+    Err(ThorLangError::RuntimeError {
+        message: format!("no native function is registered under the name {name}"),
+    })
 }
 
-pub fn register_string_methods(self_value: Value) -> HashMap<String, Value> {
-    let mut map = HashMap::new();
-
-    //primitive methods only need a name some inputs and a reference to themselves
-    //the "register function body" method can be used to register the actual calculations but not
-    //to the struct but to the FN_MAP static variable instead, making the functions easily lazy
-    //loadable in the future
-    Value::primitive_method("length", vec![], self_value.clone())
-        .register_function_body(
-            &FN_MAP,
-            Arc::new(|_, self_value, _, _, _| {
-                if let ValueType::String(self_string) = &self_value.unwrap().value {
-                    return Ok(Value::number(self_string.len() as f64));
-                }
-                Err(ThorLangError::UnknownError)
-            }),
-        )
-        .insert_to(&mut map);
-
-    Value::primitive_method("parse_number", vec![], self_value)
-        .register_function_body(
-            &FN_MAP,
-            Arc::new(|_, self_value, _, _, _| {
-                if let ValueType::String(self_value) = &self_value.unwrap().value {
-                    return match self_value.parse::<f64>() {
-                        Ok(num) => Ok(Value::number(num)),
-                        Err(_) => Err((ThorLangError::UnknownError)),
-                    };
-                }
-                Err(ThorLangError::UnknownError)
-            }),
-        )
-        .insert_to(&mut map);
+//returns the registry of the interpreter the environment belongs to
+pub fn get_registry(enclosing: &Arc<Mutex<Environment>>) -> Result<RegisteredFnMap, ThorLangError> {
+    match enclosing.lock().unwrap().get_registry() {
+        Some(registry) => Ok(registry),
+        None => Err(ThorLangError::RuntimeError {
+            message: "the environment does not belong to an interpreter".to_string(),
+        }),
+    }
+}
 
-    map
+pub fn register_string_methods(registry: &RegisteredFnMap) {
+    let mut registry = registry.lock().unwrap();
+
+    //primitive methods only need a name some inputs and a body, the value they are called on is
+    //passed to the body as self value
+    registry.register_method(
+        "string",
+        "length",
+        vec![],
+        Arc::new(|_, self_value, _, _| {
+            if let ValueType::String(self_string) = &self_value.unwrap().value {
+                return Ok(Value::number(self_string.len() as f64));
+            }
+            Err(ThorLangError::UnknownError)
+        }),
+    );
+
+    registry.register_method(
+        "string",
+        "parse_number",
+        vec![],
+        Arc::new(|_, self_value, _, _| {
+            if let ValueType::String(self_value) = &self_value.unwrap().value {
+                return match self_value.parse::<f64>() {
+                    Ok(num) => Ok(Value::number(num)),
+                    Err(_) => Err((ThorLangError::UnknownError)),
+                };
+            }
+            Err(ThorLangError::UnknownError)
+        }),
+    );
 }
 
 pub fn register_bool_methods(self_value: Value) -> HashMap<String, Value> {
@@ -195,58 +212,53 @@ pub fn register_function_methods(self_value: Value) -> HashMap<String, Value> {
     map
 }
 
-//creates only named functions and inserts the function body itself inside of the FN_MAP
-pub fn register_number_methods(self_value: Value) -> HashMap<String, Value> {
-    let mut map = HashMap::new();
-
-    Value::named_function("ceil", vec![], Some(Box::new(self_value.clone())), None, None)
-        .register_function_body(
-            &FN_MAP,
-            Arc::new(|_, self_value: Option<Value>, _, _, _| {
-                if let ValueType::Number(num) = &self_value.unwrap().value {
-                    return Ok(Value::number(num.ceil()));
-                }
-                Err(ThorLangError::UnknownError)
-            }),
-        )
-        .insert_to(&mut map);
-
-    Value::named_function("floor", vec![], Some(Box::new(self_value.clone())), None, None)
-        .register_function_body(
-            &FN_MAP,
-            Arc::new(|_, self_value: Option<Value>, _, _, _| {
-                if let ValueType::Number(num) = &self_value.unwrap().value {
-                    return Ok(Value::number(num.floor()));
-                }
-                Err(ThorLangError::UnknownError)
-            }),
-        )
-        .insert_to(&mut map);
-
+pub fn register_number_methods(registry: &RegisteredFnMap) {
+    let mut registry = registry.lock().unwrap();
 
-
-    Value::named_function("sqrt", vec![], Some(Box::new(self_value)), None, None)
-        .register_function_body(
-            &FN_MAP,
-            Arc::new(|_, self_value: Option<Value>, _, _, _| {
-                if let ValueType::Number(num) = &self_value.unwrap().value {
-                    return Ok(Value::number(num.sqrt()));
-                }
-                Err(ThorLangError::UnknownError)
-            }),
-        )
-        .insert_to(&mut map);
-
-    map
+    registry.register_method(
+        "number",
+        "ceil",
+        vec![],
+        Arc::new(|_, self_value: Option<Value>, _, _| {
+            if let ValueType::Number(num) = &self_value.unwrap().value {
+                return Ok(Value::number(num.ceil()));
+            }
+            Err(ThorLangError::UnknownError)
+        }),
+    );
+
+    registry.register_method(
+        "number",
+        "floor",
+        vec![],
+        Arc::new(|_, self_value: Option<Value>, _, _| {
+            if let ValueType::Number(num) = &self_value.unwrap().value {
+                return Ok(Value::number(num.floor()));
+            }
+            Err(ThorLangError::UnknownError)
+        }),
+    );
+
+    registry.register_method(
+        "number",
+        "sqrt",
+        vec![],
+        Arc::new(|_, self_value: Option<Value>, _, _| {
+            if let ValueType::Number(num) = &self_value.unwrap().value {
+                return Ok(Value::number(num.sqrt()));
+            }
+            Err(ThorLangError::UnknownError)
+        }),
+    );
 }
 
-pub fn register_native_functions(env: EnvState) -> HashMap<String, Value> {
+pub fn register_native_functions(env: EnvState, registry: &RegisteredFnMap) -> HashMap<String, Value> {
     let mut map = HashMap::new();
 
     Value::env_function("eval", vec!["code"], env.clone())
         .register_function_body(
-            &FN_MAP, 
-            Arc::new(|args, _, enclosing, _, env_state|{
+            registry, 
+            Arc::new(|args, _, enclosing, env_state|{
                 let env_state = env_state.unwrap();
                 
 
@@ -259,7 +271,7 @@ pub fn register_native_functions(env: EnvState) -> HashMap<String, Value> {
 
                     let mut overloadings = enclosing.lock().unwrap().get_overloadings();
 
-                    let lexed = lexer(eval_code.to_string());
+                    let lexed = lexer(eval_code.to_string())?;
 
                     let ast = parse(lexed)?;
 
@@ -278,8 +290,8 @@ pub fn register_native_functions(env: EnvState) -> HashMap<String, Value> {
 
        Value::simple_function("get_now", vec![])
         .register_function_body(
-            &FN_MAP,
-            Arc::new(|_, _, _, _, _| {
+            registry,
+            Arc::new(|_, _, _, _| {
                 let now = UNIX_EPOCH.elapsed().unwrap().as_millis() as f64;
                 Ok(Value::number(now))
             }),
@@ -288,8 +300,8 @@ pub fn register_native_functions(env: EnvState) -> HashMap<String, Value> {
 
     Value::env_function("import", vec!["namespace"], env.clone())
         .register_function_body(
-            &FN_MAP,
-            Arc::new(|args, _, _, _, env_state| {
+            registry,
+            Arc::new(|args, _, enclosing, env_state| {
                 let path = env_state.clone().unwrap().path;
 
                 let namespace = args
@@ -303,22 +315,42 @@ pub fn register_native_functions(env: EnvState) -> HashMap<String, Value> {
 
                     module_path.push(string);
 
-                    let module_text = fs::read_to_string(module_path).unwrap_or_else(|_| {
-                        panic!("module {string} does not exist in the current directory")
-                    });
+                    let module_text = match fs::read_to_string(module_path) {
+                        Ok(text) => text,
+                        Err(_) => {
+                            return ThorLangError::runtime_error(format!(
+                                "module {string} does not exist in the current directory"
+                            ))
+                        }
+                    };
+
+                    //modules run in their own interpreter, their errors are handed back to the
+                    //importing code instead of ending the program
+                    //the module shares the natives of the importing interpreter
+                    let mut builder = Interpreter::builder()
+                        .env_state(env_state.unwrap())
+                        .file(string);
 
-                    return Ok(interpret_code(module_text, env_state.unwrap().clone()));
+                    if let Some(enclosing) = enclosing {
+                        builder = builder.registry(get_registry(&enclosing)?);
+                    }
+
+                    let mut module_interpreter = builder.build();
+
+                    //the error keeps pointing into the module, the import call is added to
+                    //its trace by the importing code
+                    return module_interpreter.evaluate(&module_text);
                 } else {
-                    panic!("can only import from strings")
+                    ThorLangError::runtime_error("can only import from strings".to_string())
                 }
             }),
         )
         .insert_to(&mut map);
 
-    Value::env_function("import_lib", vec!["namespace"], env)
+    Value::env_function("import_lib", vec!["namespace"], env.clone())
         .register_function_body(
-            &FN_MAP,
-            Arc::new(|args, _, _, _, env_state| {
+            registry,
+            Arc::new(|args, _, _, env_state| {
                 let namespace = args.get("namespace").unwrap();
 
                 if let ValueType::String(path) = &namespace.value {
@@ -328,9 +360,7 @@ pub fn register_native_functions(env: EnvState) -> HashMap<String, Value> {
                     let lib_map = load_lib(path_string);
 
                     let mut ret = Value::nil();
-                    ret.value = ValueType::Object;
-
-                    ret.fields = lib_map?;
+                    ret.value = ValueType::Object(Shared::new(lib_map?));
 
                     return Ok(ret);
                 }
@@ -340,25 +370,23 @@ pub fn register_native_functions(env: EnvState) -> HashMap<String, Value> {
         )
         .insert_to(&mut map);
 
-    Value::simple_function("get_input", vec!["message"])
+    Value::env_function("get_input", vec!["message"], env.clone())
         .register_function_body(
-            &FN_MAP,
-            Arc::new(|args, _, _, _, _| {
+            registry,
+            Arc::new(|args, _, _, env_state| {
                 let message = args.get("message").unwrap();
 
+                let env_state = env_state.unwrap_or_default();
+
                 //this functions needs nil as an input if there is no message to print
                 if let ValueType::Nil = message.value {
                 } else {
-                    println!("{}", stringify_value(message.clone()));
+                    env_state.write_line(&stringify_value(message.clone()))?;
                 }
 
-                let mut input_line = String::new();
-
-                let stdin = io::stdin();
+                let input_line = env_state.read_line()?;
 
-                stdin.lock().read_line(&mut input_line).unwrap();
-
-                let ret_val = Value::string(input_line.replace("\n", ""));
+                let ret_val = Value::string(input_line);
 
                 return Ok(ret_val);
             }),
@@ -367,31 +395,19 @@ pub fn register_native_functions(env: EnvState) -> HashMap<String, Value> {
 
     Value::simple_function("type_of", vec!["value"])
         .register_function_body(
-            &FN_MAP,
-            Arc::new(|args, _, _, _, _| {
+            registry,
+            Arc::new(|args, _, _, _| {
                 let val = args.get("value").unwrap();
 
-                return Ok(Value::string(
-                    match &val.value {
-                        ValueType::String(_str) => "string",
-                        ValueType::Number(_num) => "number",
-                        ValueType::Nil => "nil",
-                        ValueType::Object => "object",
-                        ValueType::Array(_arr) => "array",
-                        ValueType::Function(_func) => "function",
-                        ValueType::Bool(_bool) => "bool",
-                        ValueType::Error(_err) => "error",
-                    }
-                    .to_string(),
-                ));
+                return Ok(Value::string(val.type_name().to_string()));
             }),
         )
         .insert_to(&mut map);
 
     Value::simple_function("stringify", vec!["value"])
         .register_function_body(
-            &FN_MAP,
-            Arc::new(|args, _, _, _, _| {
+            registry,
+            Arc::new(|args, _, _, _| {
                 let val = args.get("value").unwrap();
 
                 Ok(Value::string(stringify_value(val.clone())))
@@ -399,68 +415,66 @@ pub fn register_native_functions(env: EnvState) -> HashMap<String, Value> {
         )
         .insert_to(&mut map);
 
-    map
-}
-
-pub fn register_array_methods(self_value: Value, var_name: String) -> HashMap<String, Value> {
-    let mut map = HashMap::new();
-
-    Value::primitive_method("len", vec![], self_value.clone())
+    //arrays and objects are shared, copy returns one that has nothing in common with the original
+    Value::simple_function("copy", vec!["value"])
         .register_function_body(
-            &FN_MAP,
-            Arc::new(|_, self_value, _, _, _| {
-                if let ValueType::Array(arr) = &self_value.unwrap().value {
-                    return Ok(Value::number(arr.len() as f64));
-                }
+            registry,
+            Arc::new(|args, _, _, _| {
+                let val = args.get("value").unwrap();
 
-                Err(ThorLangError::UnknownError)
+                Ok(val.deep_copy())
             }),
         )
         .insert_to(&mut map);
 
-    Value::named_function(
+    map
+}
+
+pub fn register_array_methods(registry: &RegisteredFnMap) {
+    let mut registry = registry.lock().unwrap();
+
+    registry.register_method(
+        "array",
+        "len",
+        vec![],
+        Arc::new(|_, self_value, _, _| {
+            if let ValueType::Array(arr) = &self_value.unwrap().value {
+                return Ok(Value::number(arr.lock().len() as f64));
+            }
+
+            Err(ThorLangError::UnknownError)
+        }),
+    );
+
+    //arrays are shared, so pushing changes the array for every variable that holds it
+    registry.register_method(
+        "array",
         "push",
         vec!["value"],
-        Some(Box::new(self_value)),
-        Some(var_name),
-        None,
-    )
-    .register_function_body(
-        &FN_MAP,
-        Arc::new(|args, self_value: Option<Value>, enclosing, var_name, _| {
-            if let ValueType::Array(arr) = &self_value.unwrap().value {
+        Arc::new(|args, self_value, _, _| {
+            let self_value = self_value.unwrap();
+
+            if let ValueType::Array(arr) = &self_value.value {
                 let value = args.get("value").unwrap();
-                let mut new_arr = arr.clone();
-                new_arr.push(value.clone());
-
-                let new_arr_value = Value::array(new_arr);
-
-                if let Some(var_name) = var_name {
-                    let _ =
-                        enclosing
-                            .unwrap()
-                            .lock()
-                            .unwrap()
-                            .set(var_name, new_arr_value.clone(), 0);
-                }
+                arr.lock().push(value.clone());
 
-                return Ok(new_arr_value);
+                return Ok(self_value.clone());
             }
 
             Err(ThorLangError::UnknownError)
         }),
-    )
-    .insert_to(&mut map);
-
-    map
+    );
 }
 
 //helper function to hash values (for object retrieval still in dev)
-pub fn hash_value(val: Value) -> String {
+pub fn hash_value(val: Value, dot_token_index: usize) -> Result<String, ThorLangError> {
     return match val.value {
-        ValueType::Bool(b) => b.to_string(),
-        ValueType::Number(n) => n.to_string(),
-        ValueType::String(s) => s.to_string(),
-        _ => panic!("cannot hash {:?}", val.value),
+        ValueType::Bool(b) => Ok(b.to_string()),
+        ValueType::Number(n) => Ok(n.to_string()),
+        ValueType::String(s) => Ok(s.to_string()),
+        _ => Err(ThorLangError::UnhashableKey {
+            dot_token_index,
+            key: Box::new(val),
+        }),
     };
 }
@@ -1,12 +1,14 @@
-use type_lib::{Expression, Statement, ThorLangError, Token, TokenType};
-
-use std::collections::HashMap;
-
-//returns the previous token in the tokenlist
-fn get_previous_token<'a>(current_index: &mut usize, tokens: &'a Vec<Token>) -> &'a Token {
-    let current_token = tokens.get(*current_index - 1).unwrap();
-    current_token
-}
+use type_lib::{Arity, Expression, MatchArm, Pattern, Statement, ThorLangError, Token, TokenType};
+
+//the statement keywords the parser can continue at after an error
+const SYNC_KEYWORDS: [TokenType; 6] = [
+    TokenType::LET,
+    TokenType::FN,
+    TokenType::IF,
+    TokenType::WHILE,
+    TokenType::FOR,
+    TokenType::OVERLOAD,
+];
 
 //returns the current token in the tokenlist
 fn get_current_token<'a>(current_index: &mut usize, tokens: &'a Vec<Token>) -> &'a Token {
@@ -14,11 +16,15 @@ fn get_current_token<'a>(current_index: &mut usize, tokens: &'a Vec<Token>) -> &
     current_token
 }
 
-//consumes the current token and returns the next one
+//consumes the current token and returns the next one. the parser never moves past the eof token,
+//so code that ends in the middle of a statement fails with an unexpected token at the end of the
+//file instead of running out of tokens
 fn consume_token<'a>(current_index: &mut usize, tokens: &'a Vec<Token>) -> &'a Token {
-    *current_index += 1;
-    let temp = &tokens[*current_index];
-    temp
+    if *current_index + 1 < tokens.len() {
+        *current_index += 1;
+    }
+
+    &tokens[*current_index]
 }
 
 //returns the line (in the code) on which current token is
@@ -52,10 +58,13 @@ fn match_token<'a>(
 }
 
 //generates a list of statments and returns the global "program" list (list of ASTs) that will be
-//individually executed in eval_stmts later (in the context of a global "program")
+//individually executed in eval_stmts later (in the context of a global "program"). statements with
+//syntax errors are left out and their errors are added to errors, every parse function passes the
+//list on so blocks anywhere in the code recover the same way
 pub fn statement(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Vec<Statement>, ThorLangError> {
     let mut statements = vec![];
 
@@ -65,53 +74,59 @@ pub fn statement(
     //(which we will do anyway since we exit as soon as we encoutner EOF)
 
     while let Some(token) = tokens.get(*current_index) {
+        let statement_start = *current_index;
+
         //matches the given starting sequence to the logic behind it and recursively defines the
         //output (if there is one)
         match token.token_type {
             TokenType::OVERLOAD => {
                 consume_token(current_index, tokens);
-                ret = overload_statement(current_index, tokens)
+                ret = overload_statement(current_index, tokens, errors)
             }
             TokenType::RETURN => {
                 consume_token(current_index, tokens);
-                ret = return_statement(current_index, tokens)
+                ret = return_statement(current_index, tokens, errors)
             }
             TokenType::THROW => {
                 consume_token(current_index, tokens);
-                ret = throw_statement(current_index, tokens);
+                ret = throw_statement(current_index, tokens, errors);
+            }
+            TokenType::BREAK | TokenType::CONTINUE => {
+                consume_token(current_index, tokens);
+                ret = loop_control_statement(current_index, tokens);
             }
             TokenType::PRINT => {
                 consume_token(current_index, tokens);
-                ret = print_statement(current_index, tokens)
+                ret = print_statement(current_index, tokens, errors)
             }
             TokenType::FN => {
                 consume_token(current_index, tokens);
-                ret = function_statement(current_index, tokens)
+                ret = function_statement(current_index, tokens, errors)
             }
             TokenType::DO => {
                 consume_token(current_index, tokens);
-                ret = do_statement(current_index, tokens)
+                ret = do_statement(current_index, tokens, errors)
             }
             TokenType::IF => {
                 consume_token(current_index, tokens);
-                ret = if_statement(current_index, tokens)
+                ret = if_statement(current_index, tokens, errors)
             }
             TokenType::WHILE => {
                 consume_token(current_index, tokens);
-                ret = while_statement(current_index, tokens)
+                ret = while_statement(current_index, tokens, errors)
             }
             TokenType::FOR => {
                 consume_token(current_index, tokens);
-                ret = for_statement(current_index, tokens)
+                ret = for_statement(current_index, tokens, errors)
             }
             TokenType::LET => {
                 consume_token(current_index, tokens);
-                ret = declaration(current_index, tokens)
+                ret = declaration(current_index, tokens, errors)
             }
             TokenType::LBRACE => {
                 consume_token(current_index, tokens);
                 ret = Ok(Statement::Block {
-                    statements: statement(current_index, tokens)?,
+                    statements: statement(current_index, tokens, errors)?,
                     line: token.line,
                 })
             }
@@ -131,24 +146,76 @@ pub fn statement(
             //if there is no starting sequence, we automatically assume the next thing is an
             //expression (do statement)
             _ => {
-                ret = do_statement(current_index, tokens);
+                ret = do_statement(current_index, tokens, errors);
             }
         }
 
-        statements.push(ret?);
+        //a statement with a syntax error is left out, the error is kept and parsing goes on after
+        //it so all errors of the code are found at once
+        match ret {
+            Ok(statement) => statements.push(statement),
+            Err(error) => {
+                errors.push(error);
+                synchronize(statement_start, current_index, tokens);
+            }
+        }
     }
 
     //return all the accumulated statements as a statement list
     return Ok(statements);
 }
 
+//skips the rest of a statement that failed to parse. stops after a ; or before a } or a statement
+//keyword, braces that were opened in the statement are skipped up to the one closing them
+fn synchronize(statement_start: usize, current_index: &mut usize, tokens: &Vec<Token>) {
+    let mut depth = tokens[statement_start..*current_index]
+        .iter()
+        .fold(0, |depth: usize, token| match token.token_type {
+            TokenType::LBRACE => depth + 1,
+            TokenType::RBRACE => depth.saturating_sub(1),
+            _ => depth,
+        });
+
+    while let Some(token) = tokens.get(*current_index) {
+        let moved = *current_index > statement_start;
+
+        match &token.token_type {
+            TokenType::EOF => return,
+            TokenType::SEMICOLON if depth == 0 => {
+                consume_token(current_index, tokens);
+                return;
+            }
+            //the enclosing block needs its }
+            TokenType::RBRACE if depth == 0 => return,
+            TokenType::RBRACE if depth == 1 => {
+                consume_token(current_index, tokens);
+
+                //the block (or object) of the failed statement ends here, the ; after an object
+                //belongs to it as well
+                if get_current_token(current_index, tokens).token_type == TokenType::SEMICOLON {
+                    consume_token(current_index, tokens);
+                }
+
+                return;
+            }
+            TokenType::RBRACE => depth -= 1,
+            TokenType::LBRACE => depth += 1,
+            token_type if depth == 0 && moved && SYNC_KEYWORDS.contains(token_type) => return,
+            _ => (),
+        }
+
+        consume_token(current_index, tokens);
+    }
+}
+
 //this is not implmenented yet, but will replace "return throw("");" in the future
 fn throw_statement(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Statement, ThorLangError> {
     let throw_token_index = *current_index - 1;
-    let exception = expr(current_index, tokens)?;
+    let exception = expr(current_index, tokens, errors)?;
 
     match_token(current_index, tokens, TokenType::SEMICOLON)?;
 
@@ -162,6 +229,7 @@ fn throw_statement(
 fn overload_statement(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Statement, ThorLangError> {
     //traditional operators
     let operations = vec![
@@ -169,6 +237,9 @@ fn overload_statement(
         TokenType::MINUS,
         TokenType::STAR,
         TokenType::SLASH,
+        TokenType::PERCENT,
+        TokenType::TILDESLASH,
+        TokenType::STARSTAR,
         TokenType::BANG,
         TokenType::EQEQ,
         TokenType::GREATER,
@@ -221,7 +292,7 @@ fn overload_statement(
 
     match_token(current_index, tokens, TokenType::LBRACE)?;
 
-    let operation = statement(current_index, tokens)?;
+    let operation = statement(current_index, tokens, errors)?;
 
     Ok(Statement::Overload {
         operator,
@@ -236,31 +307,50 @@ fn overload_statement(
 fn return_statement(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Statement, ThorLangError> {
     let line = get_statement_line(current_index, tokens);
-    let expression = expr(current_index, tokens)?;
+    let expression = expr(current_index, tokens, errors)?;
     //consume and match the semicolon token
     let _ = match_token(current_index, tokens, TokenType::SEMICOLON)?;
 
     return Ok(Statement::Return { expression, line });
 }
 
+//"break;" and "continue;", whether they are inside of a loop is checked by the compiler
+fn loop_control_statement(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Statement, ThorLangError> {
+    let keyword_token_index = *current_index - 1;
+
+    match_token(current_index, tokens, TokenType::SEMICOLON)?;
+
+    if tokens[keyword_token_index].token_type == TokenType::BREAK {
+        return Ok(Statement::Break {
+            break_token_index: keyword_token_index,
+        });
+    }
+
+    return Ok(Statement::Continue {
+        continue_token_index: keyword_token_index,
+    });
+}
+
 //again rather simple just check if the right things stand at the right places and throw else, when
 //done just return a while statement object
 fn while_statement(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Statement, ThorLangError> {
     let line = get_statement_line(current_index, tokens);
 
     match_token(current_index, tokens, TokenType::LPAREN)?;
 
-    let condition = expr(current_index, tokens)?;
+    let condition = expr(current_index, tokens, errors)?;
 
     match_token(current_index, tokens, TokenType::RPAREN)?;
     match_token(current_index, tokens, TokenType::LBRACE)?;
 
-    let block = Box::new(statement(current_index, tokens)?);
+    let block = Box::new(statement(current_index, tokens, errors)?);
 
     return Ok(Statement::While {
         condition,
@@ -272,6 +362,7 @@ fn while_statement(
 fn for_statement(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Statement, ThorLangError> {
     let line = get_statement_line(current_index, tokens);
 
@@ -284,11 +375,11 @@ fn for_statement(
 
             match_token(current_index, tokens, TokenType::IN)?;
 
-            let iterator = expr(current_index, tokens)?;
+            let iterator = expr(current_index, tokens, errors)?;
 
             match_token(current_index, tokens, TokenType::LBRACE)?;
 
-            let block = statement(current_index, tokens)?;
+            let block = statement(current_index, tokens, errors)?;
 
             Ok(Statement::For {
                 iteration_variable,
@@ -296,6 +387,43 @@ fn for_statement(
                 block,
             })
         }
+        //for [key, value] in pairs {} loops over a hidden variable that is destructured at the
+        //start of every iteration
+        TokenType::LBRACK | TokenType::LBRACE => {
+            let pattern = pattern(current_index, tokens, true, errors)?;
+
+            let in_token_index = *current_index;
+            match_token(current_index, tokens, TokenType::IN)?;
+
+            let iterator = expr(current_index, tokens, errors)?;
+
+            match_token(current_index, tokens, TokenType::LBRACE)?;
+
+            let mut block = statement(current_index, tokens, errors)?;
+
+            let element = "#element#".to_string();
+
+            block.insert(
+                0,
+                Statement::Destructure {
+                    pattern,
+                    expression: Expression::Identifier {
+                        name: element.clone(),
+                        identifier_token_index: in_token_index + 1,
+                        slot: None,
+                    },
+                    line,
+                    token_index: in_token_index,
+                    slots: vec![],
+                },
+            );
+
+            Ok(Statement::For {
+                iteration_variable: TokenType::IDENTIFIER(element),
+                iterator,
+                block,
+            })
+        }
         _ => ThorLangError::unexpected_token(current, *current_index),
     }
 }
@@ -304,12 +432,13 @@ fn for_statement(
 fn if_statement(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Statement, ThorLangError> {
     let line = get_statement_line(current_index, tokens);
 
     match_token(current_index, tokens, TokenType::LPAREN)?;
 
-    let condition = expr(current_index, tokens)?;
+    let condition = expr(current_index, tokens, errors)?;
 
     match_token(current_index, tokens, TokenType::RPAREN)?;
 
@@ -317,7 +446,7 @@ fn if_statement(
     //(statement function below handles this);
     let _ = match_token(current_index, tokens, TokenType::LBRACE)?;
 
-    let then_branch = Box::new(statement(current_index, tokens)?);
+    let then_branch = Box::new(statement(current_index, tokens, errors)?);
 
     let mut else_branch = None;
 
@@ -329,8 +458,15 @@ fn if_statement(
 
     if next_token.token_type == TokenType::ELSE {
         consume_token(current_index, tokens);
-        consume_token(current_index, tokens);
-        else_branch = Some(Box::new(statement(current_index, tokens)?));
+
+        //else if is an else block that only holds the next if statement
+        if get_current_token(current_index, tokens).token_type == TokenType::IF {
+            consume_token(current_index, tokens);
+            else_branch = Some(Box::new(vec![if_statement(current_index, tokens, errors)?]));
+        } else {
+            match_token(current_index, tokens, TokenType::LBRACE)?;
+            else_branch = Some(Box::new(statement(current_index, tokens, errors)?));
+        }
     }
 
     return Ok(Statement::If {
@@ -345,9 +481,10 @@ fn if_statement(
 fn print_statement(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Statement, ThorLangError> {
     let line = get_statement_line(current_index, tokens);
-    let expression = expr(current_index, tokens)?;
+    let expression = expr(current_index, tokens, errors)?;
 
     match_token(current_index, tokens, TokenType::SEMICOLON)?;
 
@@ -359,6 +496,7 @@ fn print_statement(
 fn function_statement(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Statement, ThorLangError> {
     let line = get_statement_line(current_index, tokens);
     let token = tokens.get(*current_index).unwrap();
@@ -375,58 +513,135 @@ fn function_statement(
     //consume the identifier token
     let mut token = &consume_token(current_index, tokens).clone();
 
-    let mut args = vec![];
+    match_token(current_index, tokens, TokenType::LPAREN)?;
 
-    token = match_token(current_index, tokens, TokenType::LPAREN)?;
+    let (args, arity, mut defaults) = parameters(current_index, tokens, errors)?;
 
-    //arguments to functions could theoretically not be seperated by commas, both in declaration as
-    //in call
-    while token.token_type != TokenType::RPAREN {
-        match &token.token_type {
-            TokenType::IDENTIFIER(str) => {
-                args.push(str.clone());
-            }
-            _ => (),
-        }
-        token = consume_token(current_index, tokens);
-    }
-
-    //consume rparen
-    consume_token(current_index, tokens);
+    match_token(current_index, tokens, TokenType::RPAREN)?;
 
     match_token(current_index, tokens, TokenType::LBRACE)?;
 
-    let block = statement(current_index, tokens);
+    defaults.extend(statement(current_index, tokens, errors)?);
 
     Ok(Statement::Function {
         arguments: args,
+        arity,
         name: function_name,
-        body: Box::new(block?),
+        body: Box::new(defaults),
         line,
+        slot: None,
     })
 }
 
+//the parameters of a function or lambda up to the closing paren (or the arrow of a lambda without
+//parens): names, names with a default (b = 2) and a rest parameter at the end (...items). the
+//defaults are returned as statements that go at the start of the body
+//
+//arguments to functions could theoretically not be seperated by commas, both in declaration as in
+//call
+fn parameters(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<(Vec<String>, Arity, Vec<Statement>), ThorLangError> {
+    let mut names: Vec<String> = vec![];
+    let mut defaults = vec![];
+    let mut rest = false;
+
+    loop {
+        match get_current_token(current_index, tokens).token_type.clone() {
+            TokenType::RPAREN | TokenType::MINUS => break,
+            TokenType::COMMA => {
+                consume_token(current_index, tokens);
+            }
+            //the rest parameter has to be the last one
+            TokenType::DOT => {
+                consume_token(current_index, tokens);
+                match_token(current_index, tokens, TokenType::DOT)?;
+                match_token(current_index, tokens, TokenType::DOT)?;
+
+                let TokenType::IDENTIFIER(name) = get_current_token(current_index, tokens).token_type.clone() else {
+                    return ThorLangError::unexpected_token(TokenType::IDENTIFIER("".to_string()), *current_index);
+                };
+
+                consume_token(current_index, tokens);
+
+                names.push(name);
+                rest = true;
+                break;
+            }
+            TokenType::IDENTIFIER(name) => {
+                consume_token(current_index, tokens);
+
+                if get_current_token(current_index, tokens).token_type == TokenType::EQ {
+                    consume_token(current_index, tokens);
+
+                    defaults.push(Statement::DefaultArgument {
+                        name: name.to_string(),
+                        index: names.len(),
+                        default: expr(current_index, tokens, errors)?,
+                    });
+                }
+                //parameters without a default can't come after one with a default
+                else if !defaults.is_empty() {
+                    return ThorLangError::unexpected_token(TokenType::EQ, *current_index);
+                }
+
+                names.push(name);
+            }
+            _ => {
+                return ThorLangError::unexpected_token_of_many(
+                    vec![TokenType::IDENTIFIER("".to_string()), TokenType::RPAREN],
+                    *current_index,
+                )
+            }
+        }
+    }
+
+    let optional = defaults.len() + rest as usize;
+
+    let arity = Arity {
+        min: names.len() - optional,
+        max: if rest { None } else { Some(names.len()) },
+    };
+
+    return Ok((names, arity, defaults));
+}
+
 //do turns expressions into statements;
 fn do_statement(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Statement, ThorLangError> {
     let line = get_statement_line(current_index, tokens);
 
-    let expression = expr(current_index, tokens)?;
+    let expression = expr(current_index, tokens, errors)?;
 
-    match_token(current_index, tokens, TokenType::SEMICOLON)?;
+    //the semicolon can be left out after the last expression of a block, like in
+    //let a = if (b) { 1 } else { 2 };
+    if get_current_token(current_index, tokens).token_type != TokenType::RBRACE {
+        match_token(current_index, tokens, TokenType::SEMICOLON)?;
+    }
 
     return Ok(Statement::Do { expression, line });
 }
 
 //variables
 //"let a = 10;"
-fn declaration(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Statement, ThorLangError> {
+fn declaration(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Statement, ThorLangError> {
     let line = get_statement_line(current_index, tokens);
     let name: String;
     let mut token = tokens.get(*current_index).unwrap().clone();
 
+    if let TokenType::LBRACK | TokenType::LBRACE = token.token_type {
+        return destructuring_declaration(current_index, tokens, errors);
+    }
+
     //the first thing after let has to be a variable name
     if let TokenType::IDENTIFIER(str) = token.token_type {
         name = str;
@@ -456,7 +671,7 @@ fn declaration(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Stateme
     //variable name we encountered above
     if token.token_type == TokenType::EQ {
         let _ = consume_token(current_index, tokens).clone();
-        init = expr(current_index, tokens)?;
+        init = expr(current_index, tokens, errors)?;
     }
 
     match_token(current_index, tokens, TokenType::SEMICOLON)?;
@@ -466,21 +681,285 @@ fn declaration(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Stateme
         name,
         expression: init,
         line,
+        name_token_index: literal_token_index,
+        slot: None,
     });
 }
 
 //all of the below are part of the precedence hierarchy
 
+//if in expression position is parsed just like the statement
+fn if_expression(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let Statement::If {
+        condition,
+        then_branch,
+        else_branch,
+        ..
+    } = if_statement(current_index, tokens, errors)?
+    else {
+        return ThorLangError::unexpected_token(TokenType::IF, *current_index);
+    };
+
+    return Ok(Expression::If {
+        condition: Box::new(condition),
+        then_branch: *then_branch,
+        else_branch: else_branch.map(|else_branch| *else_branch),
+    });
+}
+
+//match value {
+//  pattern => value,
+//  pattern if guard => value,
+//}
+fn match_expression(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let match_token_index = *current_index - 1;
+
+    let value = expr(current_index, tokens, errors)?;
+
+    match_token(current_index, tokens, TokenType::LBRACE)?;
+
+    let mut arms = vec![];
+
+    while get_current_token(current_index, tokens).token_type != TokenType::RBRACE {
+        let pattern = pattern(current_index, tokens, false, errors)?;
+
+        let mut guard = None;
+
+        if get_current_token(current_index, tokens).token_type == TokenType::IF {
+            consume_token(current_index, tokens);
+            guard = Some(expr(current_index, tokens, errors)?);
+        }
+
+        match_token(current_index, tokens, TokenType::FATARROW)?;
+
+        let body = expr(current_index, tokens, errors)?;
+
+        arms.push(MatchArm {
+            pattern,
+            guard,
+            body,
+        });
+
+        //the comma after the last arm is optional
+        if get_current_token(current_index, tokens).token_type != TokenType::COMMA {
+            break;
+        }
+        consume_token(current_index, tokens);
+    }
+
+    match_token(current_index, tokens, TokenType::RBRACE)?;
+
+    return Ok(Expression::Match {
+        value: Box::new(value),
+        arms,
+        match_token_index,
+    });
+}
+
+//the type names that can be matched against (nil is matched by the nil literal)
+const TYPE_NAMES: [&str; 7] = [
+    "string", "number", "bool", "array", "object", "function", "error",
+];
+
+//patterns of match arms and of destructuring lets and fors, the latter always match (apart from
+//missing elements) so they can only be made of names, arrays and objects. their elements can have
+//defaults instead
+fn pattern(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    destructuring: bool,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Pattern, ThorLangError> {
+    let token_index = *current_index;
+    let token_type = get_current_token(current_index, tokens).token_type.clone();
+
+    consume_token(current_index, tokens);
+
+    match token_type {
+        TokenType::IDENTIFIER(name) if name == "_" => Ok(Pattern::Wildcard),
+        TokenType::IDENTIFIER(name) if !destructuring && TYPE_NAMES.contains(&name.as_str()) => {
+            let mut binding = None;
+
+            if let TokenType::IDENTIFIER(_) = get_current_token(current_index, tokens).token_type {
+                binding = Some(Box::new(pattern(current_index, tokens, false, errors)?));
+            }
+
+            Ok(Pattern::Type {
+                type_name: name,
+                binding,
+            })
+        }
+        TokenType::IDENTIFIER(name) => Ok(Pattern::Binding {
+            name,
+            name_token_index: token_index,
+        }),
+        TokenType::NUMBER(_)
+        | TokenType::STRING(_)
+        | TokenType::TRUE
+        | TokenType::FALSE
+        | TokenType::NIL
+            if !destructuring =>
+        {
+            Ok(Pattern::Literal {
+                literal: token_type,
+                literal_token_index: *current_index,
+            })
+        }
+        //negative numbers
+        TokenType::MINUS if !destructuring => {
+            let TokenType::NUMBER(number) = get_current_token(current_index, tokens).token_type.clone() else {
+                return ThorLangError::unexpected_token(TokenType::NUMBER("".to_string()), *current_index);
+            };
+
+            consume_token(current_index, tokens);
+
+            Ok(Pattern::Literal {
+                literal: TokenType::NUMBER(format!("-{}", number)),
+                literal_token_index: *current_index,
+            })
+        }
+        //[first, second, ..rest], the rest can only come last and can be left without a name
+        TokenType::LBRACK => {
+            let mut elements = vec![];
+            let mut rest = None;
+
+            while get_current_token(current_index, tokens).token_type != TokenType::RBRACK {
+                if get_current_token(current_index, tokens).token_type == TokenType::DOT {
+                    consume_token(current_index, tokens);
+                    match_token(current_index, tokens, TokenType::DOT)?;
+
+                    rest = Some(Box::new(Pattern::Wildcard));
+
+                    if let TokenType::IDENTIFIER(_) = get_current_token(current_index, tokens).token_type {
+                        rest = Some(Box::new(pattern(
+                            current_index,
+                            tokens,
+                            destructuring,
+                            errors,
+                        )?));
+                    }
+
+                    break;
+                }
+
+                elements.push(element_pattern(current_index, tokens, destructuring, errors)?);
+
+                if get_current_token(current_index, tokens).token_type != TokenType::COMMA {
+                    break;
+                }
+                consume_token(current_index, tokens);
+            }
+
+            match_token(current_index, tokens, TokenType::RBRACK)?;
+
+            Ok(Pattern::Array { elements, rest })
+        }
+        //{"key": pattern, ...}
+        TokenType::LBRACE => {
+            let mut keys = vec![];
+            let mut values = vec![];
+
+            while get_current_token(current_index, tokens).token_type != TokenType::RBRACE {
+                let TokenType::STRING(key) = get_current_token(current_index, tokens).token_type.clone() else {
+                    return ThorLangError::unexpected_token(TokenType::STRING("".to_string()), *current_index);
+                };
+
+                consume_token(current_index, tokens);
+                match_token(current_index, tokens, TokenType::COLON)?;
+
+                keys.push(key);
+                values.push(element_pattern(current_index, tokens, destructuring, errors)?);
+
+                if get_current_token(current_index, tokens).token_type != TokenType::COMMA {
+                    break;
+                }
+                consume_token(current_index, tokens);
+            }
+
+            match_token(current_index, tokens, TokenType::RBRACE)?;
+
+            Ok(Pattern::Object { keys, values })
+        }
+        _ => ThorLangError::unexpected_token_of_many(
+            vec![
+                TokenType::IDENTIFIER("_".to_string()),
+                TokenType::NUMBER("".to_string()),
+                TokenType::STRING("".to_string()),
+                TokenType::LBRACK,
+                TokenType::LBRACE,
+            ],
+            token_index,
+        ),
+    }
+}
+
+//the pattern of an element of an array or object pattern, which can be followed by a default in
+//destructuring patterns ([a, b = 0])
+fn element_pattern(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    destructuring: bool,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Pattern, ThorLangError> {
+    let element = pattern(current_index, tokens, destructuring, errors)?;
+
+    if destructuring && get_current_token(current_index, tokens).token_type == TokenType::EQ {
+        consume_token(current_index, tokens);
+
+        return Ok(Pattern::Default {
+            pattern: Box::new(element),
+            default: Box::new(expr(current_index, tokens, errors)?),
+        });
+    }
+
+    return Ok(element);
+}
+
+//let [a, b] = value; or let {"x": x} = value; (after the let)
+fn destructuring_declaration(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Statement, ThorLangError> {
+    let line = get_statement_line(current_index, tokens);
+
+    let pattern = pattern(current_index, tokens, true, errors)?;
+
+    let token_index = *current_index;
+    match_token(current_index, tokens, TokenType::EQ)?;
+
+    let expression = expr(current_index, tokens, errors)?;
+
+    match_token(current_index, tokens, TokenType::SEMICOLON)?;
+
+    return Ok(Statement::Destructure {
+        pattern,
+        expression,
+        line,
+        token_index,
+        slots: vec![],
+    });
+}
+
 //expression to listen for errors and make exceptions from them (errors that dont make the program
 //halt)
 //when the try block executes without any errors we return the value of the
 fn try_expression(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Expression, ThorLangError> {
     match_token(current_index, tokens, TokenType::LBRACE)?;
 
-    let block = statement(current_index, tokens)?;
+    let block = statement(current_index, tokens, errors)?;
 
     return Ok(Expression::Try { block });
 }
@@ -488,6 +967,7 @@ fn try_expression(
 fn on_expression(
     current_index: &mut usize,
     tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Expression, ThorLangError> {
     let current_token = get_current_token(current_index, tokens);
 
@@ -508,7 +988,7 @@ fn on_expression(
        
         if let TokenType::IDENTIFIER(_) = current_token{
 
-            let expression = expr(current_index, tokens)?;
+            let expression = expr(current_index, tokens, errors)?;
 
             variables.push(expression);
            
@@ -516,8 +996,10 @@ fn on_expression(
                 consume_token(current_index, tokens);
                 continue;
             }
-            move_on = true
         }
+
+        //anything else ends the list (without any variable this is an error below)
+        move_on = true
     }
 
     //in case there is a parenthesis around the listened variables
@@ -540,7 +1022,7 @@ fn on_expression(
         })
     }
 
-    let block = statement(current_index, tokens)?;
+    let block = statement(current_index, tokens, errors)?;
 
     Ok(Expression::On{
         block : Some(block),
@@ -550,7 +1032,11 @@ fn on_expression(
 
 }
 
-fn lambda_expression(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError>{
+fn lambda_expression(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
 
     //a lambda function looks like this: 
     //let p = :(a, b, c) -> a + b + c;
@@ -573,22 +1059,7 @@ fn lambda_expression(current_index: &mut usize, tokens: &Vec<Token>) -> Result<E
     }
 
 
-    let mut arguments : Vec<String> = Vec::new();
-
-    loop{
-        let current_token = get_current_token(current_index, tokens);
-        if let TokenType::IDENTIFIER(id) = &current_token.token_type{
-            arguments.push(id.to_string())
-        }
-        if let TokenType::RPAREN = &current_token.token_type{
-            break;;
-        }
-        if let TokenType::MINUS = &current_token.token_type{
-            break;
-        }
-
-        consume_token(current_index, tokens);
-    }
+    let (arguments, arity, mut defaults) = parameters(current_index, tokens, errors)?;
 
     //when we started with an paren around the arguments we need to close them as well
     if argument_parens{
@@ -607,21 +1078,23 @@ fn lambda_expression(current_index: &mut usize, tokens: &Vec<Token>) -> Result<E
         consume_token(current_index, tokens);
 
 
-        block = statement(current_index, tokens)?;
+        block = statement(current_index, tokens, errors)?;
     }else {
        
-        let ret_expr = expr(current_index, tokens)?;
+        let ret_expr = expr(current_index, tokens, errors)?;
 
 
         block = return_expression(ret_expr);
          
     }
 
-
+    //the defaults run before the body
+    defaults.extend(block);
 
     Ok(Expression::Lambda{
-        block,
-        arguments
+        block : defaults,
+        arguments,
+        arity
     }) 
 }
 
@@ -635,7 +1108,11 @@ fn return_expression(expr : Expression) -> Vec<Statement>{
 //matches every expression
 //precedence works on the deepest possible match i.e.
 //the more specific an expression the deeper the function goes
-fn expr(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError> {
+fn expr(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
     //here all block exprs go for example try
 
     let token = tokens.get(*current_index).unwrap();
@@ -643,48 +1120,126 @@ fn expr(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Th
     match token.token_type {
         TokenType::TRY => {
             consume_token(current_index, tokens);
-            return try_expression(current_index, tokens);
+            return try_expression(current_index, tokens, errors);
         }
         TokenType::ON => {
             consume_token(current_index, tokens);
-            return on_expression(current_index, tokens);
+            return on_expression(current_index, tokens, errors);
         }
         TokenType::COLON => {
             consume_token(current_index, tokens);
-            return lambda_expression(current_index, tokens);
+            return lambda_expression(current_index, tokens, errors);
         }
         _ => (),
     }
 
-    assign(current_index, tokens)
+    assign(current_index, tokens, errors)
 }
 
 //highest order of operational precedence i.e. the highest functionaing operator
-fn assign(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError> {
-    let expression = eq(current_index, tokens);
+fn assign(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let expression = or(current_index, tokens, errors);
 
     if let Some(token) = tokens.get(*current_index) {
-        if token.token_type == TokenType::EQ {
-            let eq_token_index = current_index.clone();
+        //compound assignments apply their operator to the current value and the new one
+        let operator = match token.token_type {
+            TokenType::EQ => None,
+            TokenType::PLUSEQ => Some(TokenType::PLUS),
+            TokenType::MINUSEQ => Some(TokenType::MINUS),
+            TokenType::STAREQ => Some(TokenType::STAR),
+            TokenType::SLASHEQ => Some(TokenType::SLASH),
+            TokenType::PERCENTEQ => Some(TokenType::PERCENT),
+            _ => return expression,
+        };
 
-            consume_token(current_index, tokens);
+        let eq_token_index = current_index.clone();
 
-            let value = assign(current_index, tokens);
+        consume_token(current_index, tokens);
 
-            return Ok(Expression::Assignment {
-                target: Box::new(expression?),
-                value: Box::new(value?),
-                eq_token_index,
-            });
-        }
+        //the value can be any expression (lambdas and try blocks included), assignments are
+        //still right associative since expr ends up in assign again
+        let value = expr(current_index, tokens, errors);
+
+        return Ok(Expression::Assignment {
+            target: Box::new(expression?),
+            value: Box::new(value?),
+            eq_token_index,
+            operator,
+        });
     }
 
     return expression;
 }
 
+//logical or (or / ||) binds weaker than and, so a or b and c is a or (b and c)
+fn or(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let mut expression = and(current_index, tokens, errors)?;
+
+    while let Some(token) = tokens.get(*current_index) {
+        if token.token_type != TokenType::OR {
+            break;
+        }
+
+        let operator_token_index = *current_index;
+
+        consume_token(current_index, tokens);
+
+        let right = and(current_index, tokens, errors)?;
+        expression = Expression::Logical {
+            left: Box::new(expression),
+            operator: TokenType::OR,
+            right: Box::new(right),
+            operator_token_index,
+        };
+    }
+
+    Ok(expression)
+}
+
+//logical and (and / &&) is one level of precedence above equality
+fn and(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let mut expression = eq(current_index, tokens, errors)?;
+
+    while let Some(token) = tokens.get(*current_index) {
+        if token.token_type != TokenType::AND {
+            break;
+        }
+
+        let operator_token_index = *current_index;
+
+        consume_token(current_index, tokens);
+
+        let right = eq(current_index, tokens, errors)?;
+        expression = Expression::Logical {
+            left: Box::new(expression),
+            operator: TokenType::AND,
+            right: Box::new(right),
+            operator_token_index,
+        };
+    }
+
+    Ok(expression)
+}
+
 //equality comparison is one level of precedence deeper (== or !=)
-fn eq(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError> {
-    let mut expression = comp(current_index, tokens)?;
+fn eq(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let mut expression = comp(current_index, tokens, errors)?;
 
     //all the operations work almost the same, we just use an expression on the left and then an
     //expression on the right and insert them together with the given operator
@@ -698,7 +1253,7 @@ fn eq(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Thor
 
                 consume_token(current_index, tokens);
 
-                let right = comp(current_index, tokens);
+                let right = comp(current_index, tokens, errors);
                 expression = Expression::Binary {
                     left: Box::new(expression),
                     operator,
@@ -714,8 +1269,12 @@ fn eq(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Thor
 }
 
 //numerical comparison is one level of precedence deeper
-fn comp(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError> {
-    let mut expression = step(current_index, tokens)?;
+fn comp(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let mut expression = step(current_index, tokens, errors)?;
 
     while let Some(token) = tokens.get(*current_index) {
         match token.token_type {
@@ -725,7 +1284,7 @@ fn comp(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Th
 
                 consume_token(current_index, tokens);
 
-                let right = step(current_index, tokens);
+                let right = step(current_index, tokens, errors);
                 expression = Expression::Binary {
                     left: Box::new(expression),
                     operator,
@@ -741,8 +1300,12 @@ fn comp(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Th
     Ok(expression)
 }
 
-fn iterate(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError> {
-    let mut expression = term(current_index, tokens)?;
+fn iterate(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let mut expression = term(current_index, tokens, errors)?;
 
     while let Some(token) = tokens.get(*current_index) {
         if let TokenType::TO = token.token_type {
@@ -751,7 +1314,7 @@ fn iterate(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression,
 
             consume_token(current_index, tokens);
 
-            let right = term(current_index, tokens)?;
+            let right = term(current_index, tokens, errors)?;
 
             expression = Expression::Binary {
                 left: Box::new(expression),
@@ -765,8 +1328,12 @@ fn iterate(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression,
     Ok(expression)
 }
 
-fn step(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError> {
-    let mut expression = iterate(current_index, tokens)?;
+fn step(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let mut expression = iterate(current_index, tokens, errors)?;
 
     if let Some(token) = tokens.get(*current_index) {
         if let TokenType::STEP = token.token_type {
@@ -775,7 +1342,7 @@ fn step(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Th
 
             consume_token(current_index, tokens);
 
-            let right = iterate(current_index, tokens)?;
+            let right = iterate(current_index, tokens, errors)?;
 
             expression = Expression::Binary {
                 left: Box::new(expression),
@@ -789,8 +1356,12 @@ fn step(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Th
 }
 
 //then comes math (first + or -)
-fn term(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError> {
-    let mut expression = factor(current_index, tokens)?;
+fn term(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let mut expression = factor(current_index, tokens, errors)?;
 
     while let Some(token) = tokens.get(*current_index) {
         match token.token_type {
@@ -800,7 +1371,7 @@ fn term(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Th
 
                 consume_token(current_index, tokens);
 
-                let right = factor(current_index, tokens);
+                let right = factor(current_index, tokens, errors);
                 expression = Expression::Binary {
                     left: Box::new(expression),
                     operator,
@@ -815,20 +1386,24 @@ fn term(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Th
     Ok(expression)
 }
 
-//then multiplication (* and /) also special characters meaning that custom operators have the same
-//precedence as multiplication
-fn factor(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError> {
-    let mut expression = unary(current_index, tokens)?;
+//then multiplication (* / % and ~/) also special characters meaning that custom operators have the
+//same precedence as multiplication
+fn factor(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let mut expression = unary(current_index, tokens, errors)?;
 
     while let Some(token) = tokens.get(*current_index) {
         match &token.token_type {
-            TokenType::STAR | TokenType::SLASH => {
+            TokenType::STAR | TokenType::SLASH | TokenType::PERCENT | TokenType::TILDESLASH => {
                 let operator = token.token_type.clone();
                 let operator_token_index = *current_index;
 
                 consume_token(current_index, tokens);
 
-                let right = unary(current_index, tokens);
+                let right = unary(current_index, tokens, errors);
                 expression = Expression::Binary {
                     left: Box::new(expression),
                     operator,
@@ -841,7 +1416,7 @@ fn factor(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression,
                 let operator_token_index = *current_index;
 
                 consume_token(current_index, tokens);
-                let right = unary(current_index, tokens);
+                let right = unary(current_index, tokens, errors);
                 expression = Expression::Binary {
                     left: Box::new(expression),
                     operator,
@@ -858,7 +1433,11 @@ fn factor(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression,
 
 //unary operations (- ! and special characters as well when their arity is 1) are recursive
 //so that we can chain them !!!!true;
-fn unary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError> {
+fn unary(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
     if let Some(token) = tokens.get(*current_index) {
         let operators = vec![
             TokenType::BANG,
@@ -880,7 +1459,7 @@ fn unary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, T
             //since we want to handle something like -(-(5));
             //we need to be able to recursively walk down that expression, therefore heres another
             //call to unary
-            let right = unary(current_index, tokens);
+            let right = unary(current_index, tokens, errors);
             return Ok(Expression::Unary {
                 operator,
                 right: Box::new(right?),
@@ -894,7 +1473,7 @@ fn unary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, T
             consume_token(current_index, tokens);
 
             //same here
-            let right = unary(current_index, tokens);
+            let right = unary(current_index, tokens, errors);
 
             return Ok(Expression::Unary {
                 operator,
@@ -904,14 +1483,47 @@ fn unary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, T
         }
     }
 
-    Ok(call(current_index, tokens)?)
+    Ok(power(current_index, tokens, errors)?)
+}
+
+//exponents bind stronger than unary operators (-2 ** 2 is -4) and are right associative (2 ** 3 ** 2
+//is 2 ** 9), the exponent itself can have a unary operator (2 ** -1)
+fn power(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let expression = call(current_index, tokens, errors)?;
+
+    if let Some(token) = tokens.get(*current_index) {
+        if let TokenType::STARSTAR = token.token_type {
+            let operator_token_index = *current_index;
+
+            consume_token(current_index, tokens);
+
+            let right = unary(current_index, tokens, errors)?;
+
+            return Ok(Expression::Binary {
+                left: Box::new(expression),
+                operator: TokenType::STARSTAR,
+                right: Box::new(right),
+                operator_token_index,
+            });
+        }
+    }
+
+    Ok(expression)
 }
 
 // needs to check whether or not the expression returned in finishcall is a function itself, and if
 // it is evaluate as well given more arguments/a call invocation i.e. "()"
 
-fn call(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError> {
-    let mut expression = primary(current_index, tokens)?;
+fn call(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    let mut expression = primary(current_index, tokens, errors)?;
 
     let mut current_token = get_current_token(current_index, tokens);
 
@@ -923,7 +1535,7 @@ fn call(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Th
             let dot_token_index = current_index.clone();
             consume_token(current_index, tokens);
 
-            let key = primary(current_index, tokens);
+            let key = primary(current_index, tokens, errors);
 
             expression = Expression::FieldCall {
                 callee: Box::new(expression),
@@ -937,7 +1549,7 @@ fn call(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Th
 
             consume_token(current_index, tokens);
 
-            expression = finish_call(current_index, tokens, expression.clone())?;
+            expression = finish_call(current_index, tokens, expression.clone(), errors)?;
 
             consume_token(current_index, tokens);
         }
@@ -946,7 +1558,7 @@ fn call(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, Th
             let lbrack_token_index = current_index.clone();
             consume_token(current_index, tokens);
 
-            let key = expr(current_index, tokens);
+            let key = expr(current_index, tokens, errors);
 
             expression = Expression::Retrieve {
                 retrievee: Box::new(expression),
@@ -968,8 +1580,13 @@ fn finish_call(
     current_index: &mut usize,
     tokens: &Vec<Token>,
     callee: Expression,
+    errors: &mut Vec<ThorLangError>,
 ) -> Result<Expression, ThorLangError> {
     let mut arguments: Vec<Expression> = vec![];
+    let mut names: Vec<String> = vec![];
+
+    //the ( is already consumed, errors of the call point at it and at the callee in front of it
+    let paren_token_index = *current_index - 1;
 
     while let Some(token) = tokens.get(*current_index) {
         match &token.token_type {
@@ -979,10 +1596,9 @@ fn finish_call(
                 return Ok(Expression::Call {
                     callee: Box::new(callee),
                     arguments,
+                    names,
 
-                    //needs to be -1 so the left token is registered instead of the right one,
-                    //makes error handling easier
-                    paren_token_index: current_index.clone() - 1,
+                    paren_token_index,
                 });
             }
             TokenType::COMMA => {
@@ -990,14 +1606,27 @@ fn finish_call(
 
                 consume_token(current_index, tokens);
             }
-            TokenType::IDENTIFIER(_str) => {
-                let argument = expr(current_index, tokens);
+            //named arguments (name: value) can only come after the positional ones
+            TokenType::IDENTIFIER(str)
+                if tokens.get(*current_index + 1).is_some_and(|next| next.token_type == TokenType::COLON) =>
+            {
+                names.push(str.to_string());
+
+                consume_token(current_index, tokens);
+                consume_token(current_index, tokens);
+
+                let argument = expr(current_index, tokens, errors);
 
                 arguments.push(argument?);
             }
-
+            _ if !names.is_empty() => {
+                return ThorLangError::unexpected_token(
+                    TokenType::IDENTIFIER("".to_string()),
+                    *current_index,
+                )
+            }
             _ => {
-                let argument = expr(current_index, tokens);
+                let argument = expr(current_index, tokens, errors);
 
                 arguments.push(argument?);
             }
@@ -1007,16 +1636,61 @@ fn finish_call(
     return ThorLangError::unexpected_token::<Expression>(TokenType::RPAREN, *current_index);
 }
 
+//"a ${b} c" is lexed as STRINGPART("a "), the tokens of b, INTERPOLATIONEND, STRING(" c"), every
+//part is followed by an expression until the closing STRING token
+fn interpolation(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
+    //the first part was already consumed by primary
+    let string_token_index = *current_index - 1;
+
+    let mut parts = vec![];
+    let mut part_index = string_token_index;
+
+    loop {
+        let part = tokens[part_index].token_type.get_content().unwrap_or_default();
+
+        parts.push(Expression::Literal {
+            literal: TokenType::STRING(part),
+            literal_token_index: part_index + 1,
+        });
+
+        if let TokenType::STRING(_) = tokens[part_index].token_type {
+            break;
+        }
+
+        parts.push(expr(current_index, tokens, errors)?);
+
+        match_token(current_index, tokens, TokenType::INTERPOLATIONEND)?;
+
+        //the lexer always puts the next part of the string after the closing brace
+        part_index = *current_index;
+        consume_token(current_index, tokens);
+    }
+
+    Ok(Expression::Interpolation {
+        parts,
+        string_token_index,
+    })
+}
+
 //the lowest precedence, returns the "atoms" , numbers, strings, arrays, ... and variables
 //
 //this is quite verbose because of the type and memory safety...
-fn primary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression, ThorLangError> {
+fn primary(
+    current_index: &mut usize,
+    tokens: &Vec<Token>,
+    errors: &mut Vec<ThorLangError>,
+) -> Result<Expression, ThorLangError> {
     if let Some(token) = tokens.get(*current_index) {
         consume_token(current_index, tokens);
         match &token.token_type {
             TokenType::IDENTIFIER(str) => Ok(Expression::Identifier {
                 name: str.to_string(),
                 identifier_token_index: current_index.clone(),
+                slot: None,
             }),
             TokenType::TRUE => Ok(Expression::Literal {
                 literal: TokenType::TRUE,
@@ -1034,6 +1708,10 @@ fn primary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression,
                 literal: TokenType::STRING(str.to_string()),
                 literal_token_index: current_index.clone(),
             }),
+            TokenType::STRINGPART(_str) => interpolation(current_index, tokens, errors),
+            //if expressions can be operands, like in 1 + if (a) { 2 } else { 3 }
+            TokenType::IF => if_expression(current_index, tokens, errors),
+            TokenType::MATCH => match_expression(current_index, tokens, errors),
             TokenType::NIL => Ok(Expression::Literal {
                 literal: TokenType::NIL,
                 literal_token_index: current_index.clone(),
@@ -1055,11 +1733,11 @@ fn primary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression,
                 let mut finished = false;
 
                 while !finished {
-                    let key = expr(current_index, tokens)?;
+                    let key = expr(current_index, tokens, errors)?;
 
                     match_token(current_index, tokens, TokenType::COLON)?;
 
-                    let value = expr(current_index, tokens)?;
+                    let value = expr(current_index, tokens, errors)?;
 
                     keys.push(key);
                     values.push(value);
@@ -1089,7 +1767,7 @@ fn primary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression,
 
                 let mut array: Vec<Expression> = vec![];
 
-                array.push(expr(current_index, tokens)?);
+                array.push(expr(current_index, tokens, errors)?);
 
                 //this again means that arrays could theoretically come about with no commas that
                 //seperate the entries, the commas are just there for readability, or distinction
@@ -1102,7 +1780,7 @@ fn primary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression,
                         }
                         TokenType::COMMA => {
                             consume_token(current_index, tokens);
-                            let value = expr(current_index, tokens);
+                            let value = expr(current_index, tokens, errors);
 
                             array.push(value?);
                         }
@@ -1120,7 +1798,7 @@ fn primary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression,
             //in case of an lparen we have a grouped expression (can be used to use + at a
             //precedence level lower than *)
             TokenType::LPAREN => {
-                let expression = expr(current_index, tokens);
+                let expression = expr(current_index, tokens, errors);
                 if let Some(token) = tokens.get(*current_index) {
                     if token.token_type == TokenType::RPAREN {
                         consume_token(current_index, tokens);
@@ -1132,6 +1810,12 @@ fn primary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression,
                     inner: Box::new(expression?),
                 })
             }
+            //code that ends where a value is expected, loops waiting for a closing token would
+            //never see it otherwise
+            TokenType::EOF => ThorLangError::unexpected_token(
+                TokenType::IDENTIFIER("".to_string()),
+                *current_index,
+            ),
             _ => Ok(Expression::Literal {
                 literal: TokenType::NIL,
                 literal_token_index: current_index.clone(),
@@ -1147,7 +1831,47 @@ fn primary(current_index: &mut usize, tokens: &Vec<Token>) -> Result<Expression,
 
 //puts all these things above into a single function
 //returns the list of asts
+//parses the tokens into the list of statements of the program or returns the first syntax error
 pub fn parse(tokens: Vec<Token>) -> Result<Vec<Statement>, ThorLangError> {
+    let (statements, errors) = parse_recovering(tokens);
+
+    match errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(statements),
+    }
+}
+
+//parses the tokens and returns every syntax error together with the statements that could be
+//parsed (statements with errors are left out)
+pub fn parse_recovering(tokens: Vec<Token>) -> (Vec<Statement>, Vec<ThorLangError>) {
     let mut current_index: usize = 0;
-    Ok(statement(&mut current_index, &tokens)?)
+    let mut statements = vec![];
+    let mut errors = vec![];
+
+    loop {
+        //statement only fails when a block fails to parse, which it recovers from itself
+        if let Ok(parsed) = statement(&mut current_index, &tokens, &mut errors) {
+            statements.extend(parsed);
+        }
+
+        //statement stops after a } that doesn't close any block (or before an else without an
+        //if), the code after it is parsed as well
+        match tokens.get(current_index).map(|token| &token.token_type) {
+            Some(TokenType::EOF) | None => break,
+            Some(TokenType::ELSE) => {
+                errors.push(ThorLangError::UnexpectedToken {
+                    expected: vec![TokenType::EOF],
+                    encountered: current_index,
+                });
+
+                consume_token(&mut current_index, &tokens);
+            }
+            Some(_) => errors.push(ThorLangError::UnexpectedToken {
+                expected: vec![TokenType::EOF],
+                encountered: current_index - 1,
+            }),
+        }
+    }
+
+    return (statements, errors);
 }
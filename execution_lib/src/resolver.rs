@@ -0,0 +1,455 @@
+use type_lib::*;
+
+use std::collections::{HashMap, HashSet};
+
+//the resolver runs between the parser and the compiler, it gives every variable that is declared
+//inside of a block, function or loop a slot (an index into the environment of that scope) and
+//tells every identifier how many environments up its variable lives. this way variables don't have
+//to be searched for by name while the code runs
+//
+//variables of the outermost scope (the globals) are still looked up by name, they can come from
+//natives, from earlier runs of the repl or from eval
+struct Scope {
+    //the slot of every variable declared in this scope, all declarations are collected when the
+    //scope is entered so functions can use variables that are declared after them
+    slots: HashMap<String, usize>,
+    //the variables whose declaration the resolver already passed
+    declared: HashSet<String>,
+    //scopes without declarations don't get their own environment at runtime
+    materialized: bool,
+    //the outermost scope of a function body, code inside of it runs later than the code around it
+    function: bool,
+}
+
+struct Resolver<'a> {
+    scopes: Vec<Scope>,
+    //the variables declared in the outermost scope of the code and the ones already passed
+    globals: HashSet<String>,
+    declared_globals: HashSet<String>,
+    //globals that already exist before the code runs
+    known_globals: &'a HashSet<String>,
+}
+
+//resolves a list of statements that runs in the outermost scope
+pub fn resolve(
+    stmts: &mut [Statement],
+    known_globals: &HashSet<String>,
+) -> Result<(), ThorLangError> {
+    let mut globals = HashMap::new();
+    collect_declarations(stmts, &mut globals)?;
+
+    let mut resolver = Resolver {
+        scopes: vec![],
+        globals: globals.into_keys().collect(),
+        declared_globals: HashSet::new(),
+        known_globals,
+    };
+
+    resolver.statements(stmts)
+}
+
+//resolves a single expression that runs in the outermost scope
+pub fn resolve_expression(
+    expr: &mut Expression,
+    known_globals: &HashSet<String>,
+) -> Result<(), ThorLangError> {
+    let mut resolver = Resolver {
+        scopes: vec![],
+        globals: HashSet::new(),
+        declared_globals: HashSet::new(),
+        known_globals,
+    };
+
+    resolver.expression(expr)
+}
+
+//blocks only get their own environment when something is declared in them, the compiler needs to
+//know this as well to know when to open a new scope. patterns that bind nothing (like [_]) don't
+//declare anything
+pub(crate) fn declares_variables(stmts: &[Statement]) -> bool {
+    stmts.iter().any(|stmt| match stmt {
+        Statement::Variable { .. } | Statement::Function { .. } => true,
+        Statement::Destructure { pattern, .. } => !pattern.bindings().is_empty(),
+        _ => false,
+    })
+}
+
+//gives every variable and function declared directly in the statements a slot, the slots that are
+//already in the map (arguments) are kept. declaring a variable twice with let is an error
+fn collect_declarations(
+    stmts: &[Statement],
+    slots: &mut HashMap<String, usize>,
+) -> Result<(), ThorLangError> {
+    let mut lets: HashSet<String> = HashSet::new();
+
+    for stmt in stmts {
+        //functions can be declared again, so they have no token to point at
+        let names = match stmt {
+            Statement::Variable {
+                name,
+                name_token_index,
+                ..
+            } => vec![(name.to_string(), Some(*name_token_index))],
+            Statement::Destructure { pattern, .. } => pattern
+                .bindings()
+                .into_iter()
+                .map(|(name, name_token_index)| (name, Some(name_token_index)))
+                .collect(),
+            Statement::Function { name, .. } => vec![(name.to_string(), None)],
+            _ => continue,
+        };
+
+        for (name, name_token_index) in names {
+            if let Some(name_token_index) = name_token_index {
+                if !lets.insert(name.to_string()) {
+                    return Err(ThorLangError::DuplicateDeclaration { name_token_index });
+                }
+            }
+
+            let next_slot = slots.len();
+            slots.entry(name).or_insert(next_slot);
+        }
+    }
+
+    Ok(())
+}
+
+impl Resolver<'_> {
+    //opens a new scope for the statements, the arguments are declared in it right away
+    fn scope(
+        &mut self,
+        stmts: &mut [Statement],
+        arguments: &[String],
+        function: bool,
+    ) -> Result<(), ThorLangError> {
+        let mut slots = HashMap::new();
+
+        for (index, argument) in arguments.iter().enumerate() {
+            slots.insert(argument.to_string(), index);
+        }
+
+        collect_declarations(stmts, &mut slots)?;
+
+        //functions and loops always get an environment at runtime, blocks only when they declare
+        //something (decided the same way as in the compiler)
+        let materialized = function || !arguments.is_empty() || declares_variables(stmts);
+
+        self.scopes.push(Scope {
+            slots,
+            declared: arguments.iter().cloned().collect(),
+            materialized,
+            function,
+        });
+
+        let resolved = self.statements(stmts);
+
+        self.scopes.pop();
+
+        resolved
+    }
+
+    //every arm gets an environment with the names its pattern binds (in the order of
+    //Pattern::bindings), binding the same name twice is an error
+    fn arm(&mut self, arm: &mut MatchArm) -> Result<(), ThorLangError> {
+        let mut slots = HashMap::new();
+
+        for (name, name_token_index) in arm.pattern.bindings() {
+            let next_slot = slots.len();
+
+            if slots.insert(name, next_slot).is_some() {
+                return Err(ThorLangError::DuplicateDeclaration { name_token_index });
+            }
+        }
+
+        self.scopes.push(Scope {
+            declared: slots.keys().cloned().collect(),
+            slots,
+            materialized: true,
+            function: false,
+        });
+
+        let mut resolved = Ok(());
+
+        if let Some(guard) = &mut arm.guard {
+            resolved = self.expression(guard);
+        }
+
+        let resolved = resolved.and_then(|_| self.expression(&mut arm.body));
+
+        self.scopes.pop();
+
+        resolved
+    }
+
+    //declares the names of a destructuring pattern in the order they are bound at runtime, so the
+    //default of an element can use the names before it ([a, b = a])
+    fn destructure(
+        &mut self,
+        pattern: &mut Pattern,
+        slots: &mut Vec<Option<usize>>,
+    ) -> Result<(), ThorLangError> {
+        match pattern {
+            Pattern::Binding { name, .. } => slots.push(self.declare(name)),
+            Pattern::Default { pattern, default } => {
+                self.expression(default)?;
+                self.destructure(pattern, slots)?;
+            }
+            Pattern::Array { elements, rest } => {
+                for element in elements.iter_mut().chain(rest.as_deref_mut()) {
+                    self.destructure(element, slots)?;
+                }
+            }
+            Pattern::Object { values, .. } => {
+                for value in values {
+                    self.destructure(value, slots)?;
+                }
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    //marks the variable as declared from here on and returns its slot (none for globals)
+    fn declare(&mut self, name: &str) -> Option<usize> {
+        match self.scopes.last_mut() {
+            Some(scope) => {
+                scope.declared.insert(name.to_string());
+                scope.slots.get(name).copied()
+            }
+            None => {
+                self.declared_globals.insert(name.to_string());
+                None
+            }
+        }
+    }
+
+    //finds the scope the variable is declared in, using a variable before its declaration is an
+    //error unless the use is inside of a function (which can only be called later)
+    fn lookup(
+        &self,
+        name: &str,
+        identifier_token_index: usize,
+    ) -> Result<Option<Slot>, ThorLangError> {
+        let mut depth = 0;
+        let mut deferred = false;
+
+        let use_before_declaration = Err(ThorLangError::UseBeforeDeclaration {
+            identifier_token_index,
+        });
+
+        for scope in self.scopes.iter().rev() {
+            if let Some(index) = scope.slots.get(name) {
+                if !deferred && !scope.declared.contains(name) {
+                    return use_before_declaration;
+                }
+
+                return Ok(Some(Slot {
+                    depth,
+                    index: *index,
+                }));
+            }
+
+            if scope.function {
+                deferred = true;
+            }
+            if scope.materialized {
+                depth += 1;
+            }
+        }
+
+        if !deferred
+            && self.globals.contains(name)
+            && !self.declared_globals.contains(name)
+            && !self.known_globals.contains(name)
+        {
+            return use_before_declaration;
+        }
+
+        Ok(None)
+    }
+
+    fn statements(&mut self, stmts: &mut [Statement]) -> Result<(), ThorLangError> {
+        for stmt in stmts {
+            self.statement(stmt)?;
+        }
+
+        Ok(())
+    }
+
+    fn statement(&mut self, stmt: &mut Statement) -> Result<(), ThorLangError> {
+        match stmt {
+            Statement::Throw { exception, .. } => self.expression(exception)?,
+            //overloadings are like functions with the operands as arguments
+            Statement::Overload {
+                operands,
+                operation,
+                ..
+            } => self.scope(operation, operands, true)?,
+            Statement::Return { expression, .. }
+            | Statement::Print { expression, .. }
+            | Statement::Do { expression, .. }
+            | Statement::DefaultArgument {
+                default: expression,
+                ..
+            } => self.expression(expression)?,
+            Statement::Break { .. } | Statement::Continue { .. } => (),
+            //the value is resolved before the variable is declared, so "let a = a;" is an error
+            Statement::Variable {
+                name,
+                expression,
+                slot,
+                ..
+            } => {
+                self.expression(expression)?;
+                *slot = self.declare(name);
+            }
+            Statement::Destructure {
+                pattern,
+                expression,
+                slots,
+                ..
+            } => {
+                self.expression(expression)?;
+
+                slots.clear();
+                self.destructure(pattern, slots)?;
+            }
+            Statement::Function {
+                name,
+                body,
+                arguments,
+                slot,
+                ..
+            } => {
+                //declared before the body so the function can call itself
+                *slot = self.declare(name);
+
+                self.scope(body, arguments, true)?
+            }
+            Statement::Block { statements, .. } => self.scope(statements, &[], false)?,
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.expression(condition)?;
+                self.scope(then_branch, &[], false)?;
+
+                if let Some(else_branch) = else_branch {
+                    self.scope(else_branch, &[], false)?;
+                }
+            }
+            Statement::While {
+                condition, block, ..
+            } => {
+                self.expression(condition)?;
+                self.scope(block, &[], false)?;
+            }
+            //the iteration variable is the first slot of the scope every iteration gets
+            Statement::For {
+                iterator,
+                iteration_variable,
+                block,
+            } => {
+                self.expression(iterator)?;
+
+                let variable = iteration_variable.get_content().unwrap_or_default();
+                self.scope(block, &[variable], false)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn expression(&mut self, expr: &mut Expression) -> Result<(), ThorLangError> {
+        match expr {
+            Expression::Try { block } => self.scope(block, &[], false)?,
+            Expression::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expression(condition)?;
+                self.scope(then_branch, &[], false)?;
+
+                if let Some(else_branch) = else_branch {
+                    self.scope(else_branch, &[], false)?;
+                }
+            }
+            Expression::Match { value, arms, .. } => {
+                self.expression(value)?;
+
+                for arm in arms {
+                    self.arm(arm)?;
+                }
+            }
+            //listeners run like functions without arguments
+            Expression::On {
+                block, variables, ..
+            } => {
+                for variable in variables {
+                    self.expression(variable)?;
+                }
+
+                if let Some(block) = block {
+                    self.scope(block, &[], true)?;
+                }
+            }
+            Expression::Lambda {
+                block, arguments, ..
+            } => self.scope(block, arguments, true)?,
+            Expression::Identifier {
+                name,
+                identifier_token_index,
+                slot,
+            } => *slot = self.lookup(name, *identifier_token_index)?,
+            Expression::Binary { left, right, .. } | Expression::Logical { left, right, .. } => {
+                self.expression(left)?;
+                self.expression(right)?;
+            }
+            Expression::Unary { right, .. } => self.expression(right)?,
+            Expression::Grouping { inner } => self.expression(inner)?,
+            Expression::Literal { .. } => (),
+            Expression::Assignment { target, value, .. } => {
+                self.expression(value)?;
+                self.expression(target)?;
+            }
+            Expression::Array { values } | Expression::Interpolation { parts: values, .. } => {
+                for value in values {
+                    self.expression(value)?;
+                }
+            }
+            Expression::Object { keys, values } => {
+                for (key, value) in keys.iter_mut().zip(values) {
+                    self.expression(key)?;
+                    self.expression(value)?;
+                }
+            }
+            Expression::Call {
+                callee, arguments, ..
+            } => {
+                self.expression(callee)?;
+
+                for argument in arguments {
+                    self.expression(argument)?;
+                }
+            }
+            Expression::Retrieve { retrievee, key, .. } => {
+                self.expression(key)?;
+                self.expression(retrievee)?;
+            }
+            //identifiers after the dot are field names and not variables
+            Expression::FieldCall { callee, key, .. } => {
+                self.expression(callee)?;
+
+                if !matches!(key.as_ref(), Expression::Identifier { .. }) {
+                    self.expression(key)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,726 @@
+use crate::{
+    assign_value, binary_operation, bind_arguments, eval_function, execute_lib_function,
+    field_value, get_registered_function, hash_value, interpolate, match_pattern, read_target,
+    retrieve_value, unary_operation, FieldKey,
+};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use type_lib::*;
+
+//an open try block, errors inside of it continue at catch with the stack and the scope it started
+//with
+struct Handler {
+    catch: usize,
+    base: usize,
+    env: Arc<Mutex<Environment>>,
+}
+
+//every call gets a frame with the chunk it runs and the environment of the current scope
+struct Frame {
+    chunk: Arc<Chunk>,
+    ip: usize,
+    env: Arc<Mutex<Environment>>,
+    //the height of the stack when the frame was entered, everything above belongs to the frame
+    base: usize,
+    //the try blocks that are open in the frame, the innermost is last
+    handlers: Vec<Handler>,
+    //the call that entered the frame (none for the outermost frame), errors leaving the frame add
+    //it to their trace
+    call: Option<TraceFrame>,
+}
+
+struct Vm<'a> {
+    stack: Vec<Value>,
+    frames: Vec<Frame>,
+    overloadings: &'a mut Overloadings,
+}
+
+//runs a compiled chunk in the given environment and returns the value it returns (nil when it
+//doesn't return anything)
+pub fn run_chunk(
+    chunk: Arc<Chunk>,
+    enclosing: Arc<Mutex<Environment>>,
+    overloadings: &mut Overloadings,
+) -> Result<Value, ThorLangError> {
+    let mut vm = Vm {
+        stack: vec![],
+        frames: vec![Frame {
+            chunk,
+            ip: 0,
+            env: enclosing,
+            base: 0,
+            handlers: vec![],
+            call: None,
+        }],
+        overloadings,
+    };
+
+    vm.run()
+}
+
+impl Vm<'_> {
+    fn run(&mut self) -> Result<Value, ThorLangError> {
+        loop {
+            match self.step() {
+                Ok(Some(value)) => return Ok(value),
+                Ok(None) => (),
+                //the closest try block turns the error into a value, without one the error leaves
+                //the vm. either way it is traced with the calls it leaves and the source of the
+                //frame it happened in
+                Err(err) => {
+                    let handler = self
+                        .frames
+                        .iter()
+                        .rposition(|frame| !frame.handlers.is_empty());
+
+                    let unwound = handler.map(|index| index + 1).unwrap_or(0);
+
+                    let calls = self.frames[unwound..]
+                        .iter()
+                        .rev()
+                        .filter_map(|frame| frame.call.clone())
+                        .collect();
+
+                    let err = err.traced(self.frame().chunk.source.clone(), calls);
+
+                    if let Some(index) = handler {
+                        self.frames.truncate(index + 1);
+
+                        let frame = self.frame();
+                        let handler = frame.handlers.pop().unwrap();
+
+                        frame.ip = handler.catch;
+                        frame.env = handler.env;
+
+                        self.stack.truncate(handler.base);
+                        self.stack.push(Value::error(err));
+                    } else {
+                        return Err(err);
+                    }
+                }
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().unwrap_or_default()
+    }
+
+    //pops the given number of values, the first value in the vector is the deepest one
+    fn pop_many(&mut self, count: usize) -> Vec<Value> {
+        let at = self.stack.len().saturating_sub(count);
+        self.stack.split_off(at)
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    //executes a single operation, returns the value of the outermost frame once it returns
+    fn step(&mut self) -> Result<Option<Value>, ThorLangError> {
+        let frame = self.frame();
+        let chunk = frame.chunk.clone();
+        let op = &chunk.code[frame.ip];
+        frame.ip += 1;
+
+        let env = frame.env.clone();
+
+        match op {
+            Op::Constant(value) => self.stack.push(value.clone()),
+            //variables with a slot are found without looking at their name, globals by name
+            Op::Get {
+                name,
+                slot,
+                identifier_token_index,
+            } => {
+                let value = match slot {
+                    Some(Slot { depth, index }) => env.lock().unwrap().get_at(*depth, *index),
+                    None => env.lock().unwrap().get(name),
+                };
+
+                if let Some(value) = value {
+                    self.stack.push(value);
+                } else {
+                    ThorLangError::unknown_value_error(*identifier_token_index)?;
+                }
+            }
+            //declaration only ever mutates the current branch of the env tree
+            Op::Define { name, slot } => {
+                let value = self.pop();
+
+                define(&env, name, *slot, value);
+            }
+            Op::Assign {
+                name,
+                slot,
+                steps,
+                eq_token_index,
+                operator,
+            } => {
+                let mut order = vec![(FieldKey::String(name.to_string()), 0)];
+
+                //the computed keys were pushed from the outside in, so the innermost is on top
+                for step in steps {
+                    let key = match step {
+                        AssignStep::Field(field, index) => {
+                            (FieldKey::String(field.to_string()), *index)
+                        }
+                        AssignStep::Hashed(index) => {
+                            (FieldKey::String(hash_value(self.pop(), *index)?), *index)
+                        }
+                        AssignStep::Key(index) => match self.pop().value {
+                            ValueType::String(str) => (FieldKey::String(str), *index),
+                            ValueType::Number(num) => (FieldKey::Int(num as i32), *index),
+                            _ => return ThorLangError::eval_error(*index).map(|_| None),
+                        },
+                    };
+
+                    order.push(key);
+                }
+
+                let mut value = self.pop();
+
+                //the keys are only computed once, the target is read through them and then
+                //assigned to (so listeners run once as well)
+                if let Some(operator) = operator {
+                    let current = read_target(&order, *slot, &env, *eq_token_index)?;
+
+                    value = binary_operation(
+                        current,
+                        operator.clone(),
+                        value,
+                        self.overloadings,
+                        *eq_token_index,
+                    )?;
+                }
+
+                let value =
+                    assign_value(order, *slot, value, *eq_token_index, env, self.overloadings)?;
+                self.stack.push(value);
+            }
+            Op::Pop => {
+                self.pop();
+            }
+            Op::Unary {
+                operator,
+                operator_token_index,
+            } => {
+                let r = self.pop();
+
+                let value = unary_operation(
+                    operator.clone(),
+                    r,
+                    self.overloadings,
+                    *operator_token_index,
+                )?;
+                self.stack.push(value);
+            }
+            Op::Binary {
+                operator,
+                operator_token_index,
+            } => {
+                let r = self.pop();
+                let l = self.pop();
+
+                let value = binary_operation(
+                    l,
+                    operator.clone(),
+                    r,
+                    self.overloadings,
+                    *operator_token_index,
+                )?;
+                self.stack.push(value);
+            }
+            Op::Logical {
+                operator,
+                end,
+                operator_token_index,
+            } => {
+                let decisive = *operator == TokenType::OR;
+
+                match self.stack.last().map(|value| &value.value) {
+                    Some(ValueType::Bool(value)) if *value == decisive => self.frame().ip = *end,
+                    Some(ValueType::Bool(_)) => {
+                        self.pop();
+                    }
+                    _ => return ThorLangError::eval_error(*operator_token_index).map(|_| None),
+                }
+            }
+            Op::Array(count) => {
+                let values = self.pop_many(*count);
+                self.stack.push(Value::array(values));
+            }
+            Op::Interpolate {
+                parts,
+                string_token_index,
+            } => {
+                let parts = self.pop_many(*parts);
+                let string = interpolate(parts, env, self.overloadings, *string_token_index)?;
+                self.stack.push(string);
+            }
+            Op::Object(count) => {
+                let mut fields = HashMap::new();
+
+                let mut pairs = self.pop_many(count * 2).into_iter();
+
+                while let (Some(key), Some(value)) = (pairs.next(), pairs.next()) {
+                    fields.insert(stringify_value(key), value);
+                }
+
+                let mut value_object = Value::nil();
+                value_object.value = ValueType::Object(Shared::new(fields));
+
+                self.stack.push(value_object);
+            }
+            Op::Retrieve(lbrack_token_index) => {
+                let retrievee = self.pop();
+                let key = self.pop();
+
+                self.stack
+                    .push(retrieve_value(retrievee, key, *lbrack_token_index)?);
+            }
+            Op::Field {
+                key,
+                dot_token_index,
+            } => {
+                let key_string = match key {
+                    Some(key) => key.to_string(),
+                    None => hash_value(self.pop(), *dot_token_index)?,
+                };
+
+                let callee_value = self.pop();
+
+                self.stack.push(field_value(callee_value, key_string, env)?);
+            }
+            Op::Call {
+                arguments,
+                names,
+                paren_token_index,
+            } => {
+                let arguments = self.pop_many(*arguments);
+                let function = self.pop();
+
+                self.call(function, arguments, names, *paren_token_index, env)?;
+            }
+            //functions close over the environment they are defined in itself (not over a copy),
+            //so every function defined in the same call sees and changes the same variables. the
+            //function can call itself since its name is declared in that environment as well
+            Op::Function {
+                function: proto,
+                slot,
+            } => {
+                let function = Value::thor_function(
+                    proto.name.to_string(),
+                    proto.arguments.to_vec(),
+                    proto.arity,
+                    proto.body.clone(),
+                    env.clone(),
+                );
+
+                define(&env, &proto.name, *slot, function);
+            }
+            Op::Lambda(proto) => {
+                self.stack.push(Value::thor_function(
+                    proto.name.to_string(),
+                    proto.arguments.to_vec(),
+                    proto.arity,
+                    proto.body.clone(),
+                    env,
+                ));
+            }
+            Op::TryStart(catch) => {
+                let base = self.stack.len();
+
+                self.frame().handlers.push(Handler {
+                    catch: *catch,
+                    base,
+                    env,
+                });
+            }
+            //whatever the block left below its value (when returning from the middle of an
+            //expression) is dropped with the handler
+            Op::TryEnd => {
+                let value = self.pop();
+                let handler = self.frame().handlers.pop().unwrap();
+
+                self.stack.truncate(handler.base);
+                self.stack.push(value);
+            }
+            Op::LeaveTry => {
+                let handler = self.frame().handlers.pop().unwrap();
+
+                self.stack.truncate(handler.base);
+            }
+            //the listener is a function without arguments that closes over the current scope
+            Op::Listen {
+                name,
+                slot,
+                listener,
+                on_token_index,
+            } => {
+                let value = self.pop();
+
+                let listener = Value::thor_function(
+                    "#lambda#".to_string(),
+                    vec![],
+                    Arity::exactly(0),
+                    listener.clone(),
+                    env.clone(),
+                );
+
+                match (slot, name) {
+                    (Some(Slot { depth, index }), _) => {
+                        env.lock().unwrap().add_listener_at(
+                            *depth,
+                            *index,
+                            listener.clone(),
+                            *on_token_index,
+                        )?;
+                    }
+                    (None, Some(name)) => {
+                        env.lock().unwrap().add_listener(
+                            name.to_string(),
+                            listener.clone(),
+                            *on_token_index,
+                        )?;
+                    }
+                    _ => (),
+                }
+
+                //values of libraries can have their own way of registering listeners
+                if let Some(mut add_func) = value.get_field("add_listener") {
+                    add_func.library = value.library.clone();
+
+                    eval_function(add_func, vec![listener], env, self.overloadings)?;
+                }
+            }
+            //this works basically like a function, except that the call operation takes place in
+            //the operator helpers
+            Op::Overload {
+                operator,
+                operands,
+                operation,
+            } => {
+                let arity = operands.len();
+
+                //any overloaded operator will only be able to access before initialized
+                //(overloaded) operators
+                let operator_info = OperationInfo {
+                    operands: operands.to_vec(),
+                    operation: operation.clone(),
+                    overloadings: self.overloadings.clone(),
+                    closure: env,
+                };
+
+                //the newest overloading is tried first
+                self.overloadings
+                    .entry((operator.clone(), arity))
+                    .or_default()
+                    .insert(0, operator_info);
+            }
+            Op::Return => {
+                let value = self.pop();
+
+                let frame = self.frames.pop().unwrap();
+                self.stack.truncate(frame.base);
+
+                if self.frames.is_empty() {
+                    return Ok(Some(value));
+                }
+
+                self.stack.push(value);
+            }
+            //the output goes to the stream of the interpreter (stdout if none is given)
+            Op::Print => {
+                let result = self.pop();
+
+                let env_state = env.lock().unwrap().get_env_state().unwrap_or_default();
+
+                if let ValueType::String(ref str) = result.value {
+                    env_state.write_line(str)?;
+                } else {
+                    env_state.write_line(&stringify_value(result))?;
+                }
+            }
+            Op::Throw(throw_token_index) => {
+                let exception = self.pop();
+
+                //throwing a caught error rethrows it as it was, its trace goes on from here
+                if let ValueType::Error(err) = exception.value {
+                    return Err(err);
+                }
+
+                return Err(ThorLangError::ThorLangException {
+                    exception: Box::new(exception),
+                    throw_token_index: *throw_token_index,
+                });
+            }
+            Op::EvalError(token_index) => {
+                ThorLangError::eval_error(*token_index)?;
+            }
+            Op::PushScope => {
+                self.frame().env = Environment::new(Some(env));
+            }
+            Op::PopScope => {
+                let enclosing = env.lock().unwrap().enclosing.clone();
+
+                if let Some(enclosing) = enclosing {
+                    self.frame().env = enclosing;
+                }
+            }
+            Op::Match {
+                pattern,
+                names,
+                next,
+            } => {
+                let value = self.stack.last().cloned().unwrap_or_default();
+                let mut bindings = vec![];
+
+                if match_pattern(pattern, &value, &mut bindings) {
+                    let arm_environment = Environment::new(Some(env));
+
+                    for (index, (name, binding)) in names.iter().zip(bindings).enumerate() {
+                        arm_environment
+                            .lock()
+                            .unwrap()
+                            .define_at(index, name, binding);
+                    }
+
+                    self.frame().env = arm_environment;
+                } else {
+                    self.frame().ip = *next;
+                }
+            }
+            Op::Destructure {
+                key,
+                default,
+                token_index,
+            } => {
+                let value = self.stack.last().cloned().unwrap_or_default();
+
+                let error = |expected: String| ThorLangError::DestructuringError {
+                    token_index: *token_index,
+                    expected,
+                    value: Box::new(value.clone()),
+                };
+
+                //values of the wrong type are always an error, missing elements and fields only
+                //when there is no default
+                let (element, missing) = match (key, &value.value) {
+                    (DestructureKey::Index(index), ValueType::Array(arr)) => (
+                        arr.lock().get(*index).cloned(),
+                        format!("an element at index {}", index),
+                    ),
+                    (DestructureKey::Rest(start), ValueType::Array(arr)) => {
+                        let rest = arr.lock().get(*start..).unwrap_or_default().to_vec();
+
+                        (Some(Value::array(rest)), String::new())
+                    }
+                    (DestructureKey::Field(field), ValueType::Object(obj)) => (
+                        obj.lock().get(field).cloned(),
+                        format!("a field \"{}\"", field),
+                    ),
+                    (DestructureKey::Field(_), _) => return Err(error("an object".to_string())),
+                    (_, _) => return Err(error("an array".to_string())),
+                };
+
+                match (element, default) {
+                    (Some(element), _) => self.stack.push(element),
+                    (None, Some(default)) => self.frame().ip = *default,
+                    (None, None) => return Err(error(missing)),
+                }
+            }
+            Op::NoMatch(match_token_index) => {
+                return Err(ThorLangError::NonExhaustiveMatch {
+                    match_token_index: *match_token_index,
+                    value: Box::new(self.pop()),
+                });
+            }
+            Op::SkipDefault { index, skip } => {
+                let given = env
+                    .lock()
+                    .unwrap()
+                    .slots
+                    .get(*index)
+                    .is_some_and(Option::is_some);
+
+                if given {
+                    self.frame().ip = *skip;
+                }
+            }
+            Op::Swap => {
+                let len = self.stack.len();
+
+                self.stack.swap(len - 2, len - 1);
+            }
+            Op::Jump(target) => {
+                self.frame().ip = *target;
+            }
+            Op::Branch {
+                on_false,
+                otherwise,
+            } => match self.pop().value {
+                ValueType::Bool(true) => (),
+                ValueType::Bool(false) => self.frame().ip = *on_false,
+                _ => self.frame().ip = *otherwise,
+            },
+            Op::Iterate { variable, exit } => {
+                let len = self.stack.len();
+
+                let index = self.stack[len - 1].to_f64().unwrap_or_default() as usize;
+
+                let element = match &self.stack[len - 2].value {
+                    ValueType::Array(arr) => arr.lock().get(index).cloned(),
+                    _ => None,
+                };
+
+                if let Some(element) = element {
+                    self.stack[len - 1] = Value::number((index + 1) as f64);
+
+                    let intermediate_environment = Environment::new(Some(env));
+
+                    intermediate_environment
+                        .lock()
+                        .unwrap()
+                        .define_at(0, variable, element);
+
+                    self.frame().env = intermediate_environment;
+                } else {
+                    self.stack.truncate(len - 2);
+                    self.frame().ip = *exit;
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    //thorfunctions get a new frame, natives are called right away and their result is pushed
+    fn call(
+        &mut self,
+        function: Value,
+        arguments: Vec<Value>,
+        names: &[String],
+        paren_token_index: usize,
+        env: Arc<Mutex<Environment>>,
+    ) -> Result<(), ThorLangError> {
+        let ValueType::Function(callee) = &function.value else {
+            return ThorLangError::unkown_function_error(paren_token_index).map(|_| ());
+        };
+
+        let needed_arguments = callee.get_args();
+
+        let arguments = bind_arguments(
+            &needed_arguments,
+            callee.arity(),
+            arguments,
+            names,
+            paren_token_index,
+        )?;
+
+        //the call site is in the chunk of the current frame
+        let call = TraceFrame {
+            function: callee.get_function_name(),
+            call_token_index: Some(paren_token_index),
+            source: self.frame().chunk.source.clone(),
+        };
+
+        if let ValueType::Function(Function::ThorFunction { body, closure, .. }) = &function.value {
+            //the function body runs in an environment that closes over the closure of the
+            //function and holds the arguments in its first slots, the ones that were left out are
+            //filled in by their defaults at the start of the body
+            let function_env = Environment::new(Some(closure.clone()));
+
+            for (index, (name, value)) in needed_arguments.iter().zip(arguments).enumerate() {
+                if let Some(value) = value {
+                    function_env.lock().unwrap().define_at(index, name, value);
+                }
+            }
+
+            let base = self.stack.len();
+
+            self.frames.push(Frame {
+                chunk: body.clone(),
+                ip: 0,
+                env: function_env,
+                base,
+                handlers: vec![],
+                call: Some(call),
+            });
+
+            return Ok(());
+        }
+
+        //natives don't get a frame, their errors are traced with their call right away
+        let source = call.source.clone();
+        let traced = |err: ThorLangError| err.traced(source.clone(), vec![call.clone()]);
+
+        //natives get their arguments by name
+        let mut eval_args: HashMap<String, Value> = needed_arguments
+            .into_iter()
+            .zip(arguments)
+            .map(|(name, value)| (name, value.unwrap_or_default()))
+            .collect();
+
+        match &function.value {
+            ValueType::Function(Function::LibFunction { self_value, .. }) => {
+                //copy all overloadings to the environment
+                env.lock()
+                    .unwrap()
+                    .set_overloadings(self.overloadings.clone());
+
+                if let Some(self_value) = self_value {
+                    eval_args.insert("self_value".to_string(), *self_value.clone());
+                }
+
+                let value = execute_lib_function(function, eval_args, env, self.overloadings)
+                    .map_err(traced)?;
+                self.stack.push(value);
+            }
+            ValueType::Function(Function::NamedFunction {
+                name,
+                self_value,
+                env_state,
+                ..
+            }) => {
+                //copy all overloadings to the environment
+                env.lock()
+                    .unwrap()
+                    .set_overloadings(self.overloadings.clone());
+
+                let body = get_registered_function(&env, name.to_string(), self_value.as_deref())?;
+
+                let value = body(
+                    eval_args,
+                    self_value.as_ref().map(|self_value| *self_value.clone()),
+                    Some(env),
+                    env_state.clone(),
+                )
+                .map_err(traced)?;
+                self.stack.push(value);
+            }
+            _ => {
+                ThorLangError::unkown_function_error(paren_token_index)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//variables with a slot go into their slot of the current environment, globals are declared by name
+fn define(env: &Arc<Mutex<Environment>>, name: &str, slot: Option<usize>, value: Value) {
+    match slot {
+        Some(index) => env.lock().unwrap().define_at(index, name, value),
+        None => {
+            env.lock()
+                .unwrap()
+                .values
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), value);
+        }
+    }
+}
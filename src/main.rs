@@ -1,29 +1,73 @@
-use execution_lib::interpret_code;
+use execution_lib::Interpreter;
 use std::env;
 use std::fs;
+use std::io::{self, IsTerminal};
+use std::process;
 use type_lib::EnvState;
 
+mod repl;
+
+use repl::repl;
+
+//how errors of thorlang run are printed
+#[derive(PartialEq)]
+enum MessageFormat {
+    Human,
+    //one json object per error on stderr, for editors and other tools
+    Json,
+}
+
+fn print_usage() {
+    println!("usage:");
+    println!("  thorlang run <file>   runs the given .thor file");
+    println!("  thorlang repl         starts an interactive session");
+    println!();
+    println!("options:");
+    println!("  --message-format=<human|json>   how errors are printed (human by default)");
+}
+
+//errors are colored when they are printed to a terminal, unless NO_COLOR is set
+pub fn color_output() -> bool {
+    io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none()
+}
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+
+    //options can be put anywhere, everything else is positional
+    let mut message_format = MessageFormat::Human;
+
+    for option in args.iter().filter(|arg| arg.starts_with("--")) {
+        message_format = match option.as_str() {
+            "--message-format=human" => MessageFormat::Human,
+            "--message-format=json" => MessageFormat::Json,
+            _ => {
+                println!("unknown option {option}\n");
+                print_usage();
+                process::exit(1);
+            }
+        };
+    }
+
+    args.retain(|arg| !arg.starts_with("--"));
 
     let mut current_dir =
         env::current_dir().expect("something went wrong reading the current directory");
 
     //this will be the entry point of the cli
-    match args.get(1).unwrap().as_str() {
-        "run" => {
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("run") => {
             //just run files ending with .thor
 
             if let Some(filename) = args.get(2) {
-                let mut filename = filename.clone().to_owned();
+                let mut filename = filename.clone();
 
                 //tries to append the .thor filetype to allow for only putting in the filename
-                if filename.contains(".thor") {
-                } else {
+                if !filename.contains(".thor") {
                     filename += ".thor";
                 }
 
-                current_dir.push(filename);
+                current_dir.push(&filename);
                 let file_dir = current_dir.clone();
                 //remove
                 current_dir.pop();
@@ -32,13 +76,50 @@ fn main() {
                 //the environment of
                 //1. the executable
                 //2. the file thats run
-                let env = EnvState { path: current_dir };
+                let env = EnvState::new(current_dir);
 
                 let file_text = fs::read_to_string(file_dir).expect("no such file found");
 
-                interpret_code(file_text, env);
+                let mut interpreter = Interpreter::builder()
+                    .env_state(env)
+                    .file(&filename)
+                    .build();
+
+                //all syntax errors are shown at once, the code only runs without any
+                let mut diagnostics = Interpreter::check(&file_text);
+
+                for diagnostic in &mut diagnostics {
+                    diagnostic.file = Some(filename.clone());
+                }
+
+                if diagnostics.is_empty() {
+                    if let Err(diagnostic) = interpreter.run(&file_text) {
+                        diagnostics.push(diagnostic);
+                    }
+                }
+
+                for diagnostic in &diagnostics {
+                    match message_format {
+                        MessageFormat::Human => {
+                            println!("\n{}\n", diagnostic.render(color_output()))
+                        }
+                        MessageFormat::Json => eprintln!("{}", diagnostic.to_json(&filename)),
+                    }
+                }
+
+                if !diagnostics.is_empty() {
+                    process::exit(1);
+                }
+            } else {
+                print_usage();
             }
         }
-        _ => {}
+        Some("repl") => {
+            //imports in the repl are resolved relative to the directory it was started in
+            let env = EnvState::new(current_dir);
+
+            repl(env);
+        }
+        _ => print_usage(),
     }
 }
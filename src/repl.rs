@@ -0,0 +1,132 @@
+use execution_lib::{diagnose, lexer, parse, Interpreter};
+use type_lib::*;
+
+use std::io::{self, BufRead, Write};
+
+use crate::color_output;
+
+//what the repl should do with the text the user has typed in so far
+enum InputState {
+    //the input can be run as it is
+    Complete(String),
+    //the input is not finished yet (open braces, missing semicolon after a try block, ...)
+    Incomplete,
+    //the input is finished but contains a syntax error
+    Invalid(ThorLangError),
+}
+
+//counts how many braces, brackets and parenthesis are still open, if this is above zero the user is
+//still typing a block (or an array, object, argument list...)
+fn open_delimiters(tokens: &[Token]) -> i32 {
+    let mut depth = 0;
+
+    for token in tokens {
+        match token.token_type {
+            TokenType::LBRACE | TokenType::LBRACK | TokenType::LPAREN => depth += 1,
+            TokenType::RBRACE | TokenType::RBRACK | TokenType::RPAREN => depth -= 1,
+            _ => (),
+        }
+    }
+
+    depth
+}
+
+//returns true when the parser stopped because it ran into the end of the input
+fn fails_at_eof(tokens: &[Token], error: &ThorLangError) -> bool {
+    if let ThorLangError::UnexpectedToken { encountered, .. } = error {
+        if let Some(token) = tokens.get(*encountered) {
+            return token.token_type == TokenType::EOF;
+        }
+    }
+    false
+}
+
+fn check_input(text: &str) -> InputState {
+    //a string that is still open continues on the next line
+    let tokens = match lexer(text.to_string()) {
+        Ok(tokens) => tokens,
+        Err(ThorLangError::UnterminatedString { .. }) => return InputState::Incomplete,
+        Err(err) => return InputState::Invalid(err),
+    };
+
+    if open_delimiters(&tokens) > 0 {
+        return InputState::Incomplete;
+    }
+
+    let error = match parse(tokens.clone()) {
+        Ok(_) => return InputState::Complete(text.to_string()),
+        Err(err) => err,
+    };
+
+    if !fails_at_eof(&tokens, &error) {
+        return InputState::Invalid(error);
+    }
+
+    //bare expressions (and try blocks) need a semicolon to be statements, in the repl we allow
+    //omitting it, if the input parses with one we just append it
+    let with_semicolon = text.trim_end().to_string() + ";";
+
+    if lexer(with_semicolon.clone()).and_then(parse).is_ok() {
+        return InputState::Complete(with_semicolon);
+    }
+
+    InputState::Incomplete
+}
+
+//interactive mode, keeps one interpreter (and so one global environment and the overloadings) alive
+//between inputs
+pub fn repl(env: EnvState) {
+    let mut interpreter = Interpreter::builder().env_state(env).build();
+
+    let stdin = io::stdin();
+    let mut buffer = String::new();
+
+    println!("thorlang repl, press ctrl-d to exit");
+
+    loop {
+        //a different prompt shows that the repl is waiting for the rest of the input
+        let prompt = if buffer.is_empty() { "> " } else { ". " };
+        print!("{prompt}");
+        let _ = io::stdout().flush();
+
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => {
+                println!();
+                break;
+            }
+            Ok(_) => (),
+        }
+
+        buffer += &line;
+
+        if buffer.trim().is_empty() {
+            buffer.clear();
+            continue;
+        }
+
+        let text = match check_input(&buffer) {
+            InputState::Incomplete => continue,
+            InputState::Invalid(err) => {
+                //lexer errors don't need the tokens
+                let tokens = lexer(buffer.clone()).unwrap_or_default();
+
+                let diagnostic = diagnose(&buffer, &tokens, err);
+
+                println!("\n{}\n", diagnostic.render(color_output()));
+                buffer.clear();
+                continue;
+            }
+            InputState::Complete(text) => text,
+        };
+
+        buffer.clear();
+
+        //the interpreter returns the value of a trailing bare expression, which is what we print
+        match interpreter.run(&text) {
+            Ok(value) if value.value != ValueType::Nil => println!("{}", stringify_value(value)),
+            Ok(_) => (),
+            Err(diagnostic) => println!("\n{}\n", diagnostic.render(color_output())),
+        }
+    }
+}
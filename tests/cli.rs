@@ -0,0 +1,213 @@
+//the thorlang binary itself
+use serde_json::Value;
+
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+//runs the code as a file with the given options
+fn run(name: &str, code: &str, options: &[&str]) -> Output {
+    let dir = std::env::temp_dir().join(format!("thorlang-cli-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let file = dir.join(format!("{name}.thor"));
+    fs::write(&file, code).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_thorlang"))
+        .arg("run")
+        .arg(&file)
+        .args(options)
+        .output()
+        .unwrap();
+
+    fs::remove_file(&file).unwrap();
+
+    output
+}
+
+//types the input into the repl and closes it at the end
+fn repl(input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_thorlang"))
+        .arg("repl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+//every line of stderr as json
+fn json_errors(output: &Output) -> Vec<Value> {
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn json_errors_are_written_to_stderr() {
+    let output = run(
+        "json_runtime",
+        "print \"before\";\nprint 1 + missing;",
+        &["--message-format=json"],
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "before\n");
+
+    let errors = json_errors(&output);
+    assert_eq!(errors.len(), 1);
+
+    let error = &errors[0];
+    assert!(error["file"]
+        .as_str()
+        .unwrap()
+        .ends_with("json_runtime.thor"));
+    assert_eq!(error["severity"], "error");
+    assert_eq!(error["code"], "E0011");
+    assert_eq!(
+        error["message"],
+        "the value `missing` is not found in the current scope"
+    );
+    assert_eq!(
+        (&error["line"], &error["column"]),
+        (&Value::from(2), &Value::from(11))
+    );
+    assert_eq!(
+        (&error["end_line"], &error["end_column"]),
+        (&Value::from(2), &Value::from(18))
+    );
+    assert_eq!(error["notes"], Value::Array(vec![]));
+    assert!(error["rendered"]
+        .as_str()
+        .unwrap()
+        .starts_with("error[E0011]"));
+
+    let spans = error["spans"].as_array().unwrap();
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0]["byte_start"], 26);
+    assert_eq!(spans[0]["byte_end"], 33);
+    assert_eq!(spans[0]["primary"], true);
+    assert_eq!(spans[0]["label"], "not found");
+}
+
+#[test]
+fn json_errors_have_all_labels_and_notes() {
+    let output = run(
+        "json_syntax",
+        "let a = (1 + 2;\nprint \"a\";",
+        &["--message-format=json"],
+    );
+    let errors = json_errors(&output);
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["code"], "E0003");
+
+    let spans = errors[0]["spans"].as_array().unwrap();
+    assert_eq!(spans.len(), 2);
+    assert_eq!(spans[0]["primary"], true);
+    assert_eq!(spans[1]["primary"], false);
+    assert_eq!(spans[1]["label"], "after this");
+
+    let output = run("json_escape", "print \"\\q\";", &["--message-format=json"]);
+    let errors = json_errors(&output);
+
+    assert_eq!(errors[0]["code"], "E0001");
+    assert_eq!(errors[0]["notes"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn json_errors_at_the_end_of_the_code_point_at_it() {
+    let output = run(
+        "json_truncated",
+        "let a = 1;\nlet x = ",
+        &["--message-format=json"],
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+
+    //json_errors fails on anything that isn't json, like the message of a panic
+    let errors = json_errors(&output);
+    assert_eq!(errors.len(), 1);
+
+    let error = &errors[0];
+    assert_eq!(error["code"], "E0003");
+    assert_eq!(
+        error["message"],
+        "expected identifier, found the end of the code"
+    );
+    assert_eq!(
+        (&error["line"], &error["column"]),
+        (&Value::from(2), &Value::from(9))
+    );
+
+    //the end of the code is an empty span after the last character
+    let spans = error["spans"].as_array().unwrap();
+    assert_eq!(spans[0]["byte_start"], 19);
+    assert_eq!(spans[0]["byte_end"], 19);
+    assert_eq!(spans[0]["primary"], true);
+}
+
+#[test]
+fn human_errors_are_rendered_to_stdout() {
+    let output = run("human", "print missing;", &[]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stderr.is_empty());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("error[E0011]"));
+}
+
+#[test]
+fn the_repl_waits_for_the_rest_of_an_expression() {
+    let output = repl("let x = 1 +\n2;\nx\n");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stderr.is_empty());
+
+    //the second line is typed after the continuation prompt
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("> . > 3\n"));
+}
+
+#[test]
+fn the_repl_keeps_going_after_syntax_errors() {
+    let output = repl("let = 1;\nlet y = 2 2;\nlet z = [3,\n4];\nz\n");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stderr.is_empty());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("error[").count(), 2);
+    assert!(stdout.contains("[3, 4]"));
+}
+
+#[test]
+fn libraries_that_cant_be_loaded_are_errors() {
+    let output = run(
+        "missing_lib",
+        "import_lib(\"missing.so\");",
+        &["--message-format=json"],
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    //the reason is part of the error instead of being printed
+    assert!(output.stdout.is_empty());
+
+    let errors = json_errors(&output);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["code"], "E0020");
+    assert!(errors[0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("missing.so can't be loaded"));
+}
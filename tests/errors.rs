@@ -0,0 +1,240 @@
+//errors come back from the interpreter as diagnostics that point at the code causing them
+use execution_lib::{Diagnostic, Interpreter};
+use type_lib::ThorLangError;
+
+fn error(code: &str) -> Diagnostic {
+    Interpreter::builder().build().run(code).unwrap_err()
+}
+
+//line and column of the primary label
+fn position(diagnostic: &Diagnostic) -> Option<(i32, i32)> {
+    diagnostic
+        .primary
+        .as_ref()
+        .map(|label| (label.span.line, label.span.column))
+}
+
+#[test]
+fn and_or_need_bools() {
+    //the left side is checked before the right one runs
+    let diagnostic = error("let a = 1 and true;");
+    assert!(matches!(
+        diagnostic.error.untraced(),
+        ThorLangError::EvalError { .. }
+    ));
+    assert_eq!(position(&diagnostic), Some((1, 11)));
+
+    let diagnostic = error("let a = true;\nlet b = a or nil;\nlet c = a and \"yes\";");
+    assert!(matches!(
+        diagnostic.error.untraced(),
+        ThorLangError::EvalError { .. }
+    ));
+    assert_eq!(position(&diagnostic), Some((3, 11)));
+
+    let diagnostic = error("let a = false || [];");
+    assert!(matches!(
+        diagnostic.error.untraced(),
+        ThorLangError::EvalError { .. }
+    ));
+    assert_eq!(position(&diagnostic), Some((1, 15)));
+}
+
+#[test]
+fn and_or_dont_check_skipped_sides() {
+    let mut interpreter = Interpreter::builder().build();
+
+    assert_eq!(
+        interpreter.run("false and 1;").unwrap().to_bool(),
+        Some(false)
+    );
+    assert_eq!(
+        interpreter.run("true or missing;").unwrap().to_bool(),
+        Some(true)
+    );
+}
+
+#[test]
+fn diagnostics_have_a_code_and_labels() {
+    let diagnostic = error("let a = (1 + 2;");
+
+    assert_eq!(diagnostic.code, "E0003");
+    assert_eq!(diagnostic.message, "expected ), found `;`");
+
+    let primary = diagnostic.primary.as_ref().unwrap();
+    assert_eq!(primary.message, "expected )");
+    assert_eq!((primary.span.offset, primary.span.length), (14, 1));
+
+    assert_eq!(diagnostic.labels.len(), 1);
+    assert_eq!(diagnostic.labels[0].message, "after this");
+    assert_eq!(diagnostic.labels[0].span.column, 14);
+}
+
+#[test]
+fn rendered_diagnostics() {
+    assert_eq!(
+        error("let a = 1;\nprint a + missing;").to_string(),
+        "error[E0011]: the value `missing` is not found in the current scope
+ --> 2:11
+  |
+2 | print a + missing;
+  |           ^^^^^^^ not found"
+    );
+
+    assert_eq!(
+        error("let a = (1 + 2;").to_string(),
+        "error[E0003]: expected ), found `;`
+ --> 1:15
+  |
+1 | let a = (1 + 2;
+  |              - after this
+  |               ^ expected )"
+    );
+}
+
+#[test]
+fn rendered_lexer_errors_have_notes() {
+    assert_eq!(
+        error("print \"bad \\q\";").to_string(),
+        "error[E0001]: invalid escape sequence `\\q` in string
+ --> 1:12
+  |
+1 | print \"bad \\q\";
+  |            ^^ unknown escape
+  |
+  = note: valid escapes are \\n, \\t, \\\\, \\\", \\$ and \\u{...}"
+    );
+}
+
+#[test]
+fn underlines_line_up_with_tabs_and_multi_byte_characters() {
+    let rendered = error("fn f(){\n\tprint \"é\" + missing;\n}\nf();").to_string();
+
+    assert!(rendered.contains("2 | \tprint \"é\" + missing;\n  | \t            ^^^^^^^ not found"));
+}
+
+#[test]
+fn colors_are_only_used_when_asked_for() {
+    let diagnostic = error("print missing;");
+
+    assert!(!diagnostic.render(false).contains('\x1b'));
+    assert!(diagnostic
+        .render(true)
+        .starts_with("\x1b[1;31merror[E0011]"));
+}
+
+#[test]
+fn check_reports_every_syntax_error() {
+    let diagnostics = Interpreter::check("let a = 1\nprint a;\nlet = 5;\nprint (1;\nprint a;");
+
+    let positions: Vec<Option<(i32, i32)>> = diagnostics.iter().map(position).collect();
+    assert_eq!(positions, vec![Some((2, 1)), Some((3, 5)), Some((4, 9))]);
+    assert!(diagnostics
+        .iter()
+        .all(|diagnostic| diagnostic.code == "E0003"));
+
+    assert!(Interpreter::check("let a = 1;\nprint a;").is_empty());
+}
+
+const NESTED_CALLS: &str = "fn inner(x){
+\treturn x + missing;
+}
+
+fn outer(){
+\treturn inner(1);
+}
+
+let f = :() -> outer();
+f();";
+
+#[test]
+fn traces_have_every_call() {
+    let diagnostic = Interpreter::builder()
+        .file("main.thor")
+        .build()
+        .run(NESTED_CALLS)
+        .unwrap_err();
+
+    assert_eq!(diagnostic.file.as_deref(), Some("main.thor"));
+    assert_eq!(position(&diagnostic), Some((2, 13)));
+
+    let calls: Vec<String> = diagnostic
+        .trace
+        .iter()
+        .map(|call| {
+            let span = call.span.unwrap();
+
+            format!(
+                "{} {}:{}:{}",
+                call.function,
+                call.file.as_deref().unwrap_or("?"),
+                span.line,
+                span.column
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        calls,
+        vec![
+            "inner main.thor:6:9",
+            "outer main.thor:9:16",
+            "#lambda# main.thor:10:1"
+        ]
+    );
+}
+
+#[test]
+fn rendered_traces() {
+    let diagnostic = Interpreter::builder()
+        .file("main.thor")
+        .build()
+        .run(NESTED_CALLS)
+        .unwrap_err();
+
+    assert!(diagnostic.to_string().ends_with(
+        "  = trace: in `inner`, called at main.thor:6:9
+  = trace: in `outer`, called at main.thor:9:16
+  = trace: in `#lambda#`, called at main.thor:10:1"
+    ));
+}
+
+#[test]
+fn errors_outside_of_functions_have_no_trace() {
+    let diagnostic = error("fn f(){ return 1; }\nf();\nprint missing;");
+
+    assert!(diagnostic.trace.is_empty());
+    assert!(!diagnostic.to_string().contains("trace"));
+}
+
+#[test]
+fn check_reports_code_that_ends_early() {
+    let diagnostics = Interpreter::check("let a = 1\nprint a;\nlet x = ");
+
+    //the errors before the end are kept
+    let positions: Vec<Option<(i32, i32)>> = diagnostics.iter().map(position).collect();
+    assert_eq!(positions, vec![Some((2, 1)), Some((3, 9))]);
+    assert_eq!(
+        diagnostics[1].message,
+        "expected identifier, found the end of the code"
+    );
+}
+
+#[test]
+fn check_handles_code_cut_off_anywhere() {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    for script in [
+        "match.thor",
+        "destructuring.thor",
+        "interpolation.thor",
+        "closures.thor",
+    ] {
+        let code = std::fs::read_to_string(root.join("thor_examples").join(script)).unwrap();
+
+        //every prefix ends in the middle of something, the parser has to stop at the end of the
+        //code without panicking or looping forever
+        for (end, _) in code.char_indices() {
+            Interpreter::check(&code[..end]);
+        }
+    }
+}
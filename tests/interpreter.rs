@@ -0,0 +1,100 @@
+//the interpreter as it is used by embedders
+use execution_lib::Interpreter;
+use type_lib::ThorLangError;
+
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn returns_the_value_of_a_trailing_expression() {
+    let mut interpreter = Interpreter::builder().build();
+
+    let value = interpreter.run("let a = 5; a * 2;").unwrap();
+
+    assert_eq!(value.to_f64(), Some(10.0));
+}
+
+#[test]
+fn returns_the_value_of_a_top_level_return() {
+    let mut interpreter = Interpreter::builder().build();
+
+    let value = interpreter.run("return 3; 4;").unwrap();
+
+    assert_eq!(value.to_f64(), Some(3.0));
+}
+
+#[test]
+fn keeps_globals_between_runs() {
+    let mut interpreter = Interpreter::builder().build();
+
+    interpreter.run("let counter = 1;").unwrap();
+    interpreter.run("counter = counter + 1;").unwrap();
+
+    assert_eq!(interpreter.run("counter;").unwrap().to_f64(), Some(2.0));
+}
+
+#[test]
+fn returns_errors_as_diagnostics() {
+    let mut interpreter = Interpreter::builder().build();
+
+    let diagnostic = interpreter.run("let a = 1;\nprint missing;").unwrap_err();
+
+    assert!(matches!(
+        diagnostic.error.untraced(),
+        ThorLangError::UnknownValueError { .. }
+    ));
+    assert_eq!(diagnostic.primary.map(|label| label.span.line), Some(2));
+}
+
+#[test]
+fn leaves_the_panic_hook_alone() {
+    let called = Arc::new(AtomicBool::new(false));
+    let hook_called = called.clone();
+
+    panic::set_hook(Box::new(move |_| hook_called.store(true, Ordering::SeqCst)));
+
+    let mut interpreter = Interpreter::builder().build();
+    assert!(interpreter.run("print missing;").is_err());
+
+    //the hook of the host is still the one that gets called
+    let _ = panic::catch_unwind(|| panic!("host panic"));
+    let _ = panic::take_hook();
+
+    assert!(called.load(Ordering::SeqCst));
+}
+
+#[test]
+fn evaluate_returns_values() {
+    let mut interpreter = Interpreter::builder().build();
+
+    let value = interpreter.evaluate("fn double(x){ return x * 2; }\ndouble(21);");
+
+    assert_eq!(value.unwrap().to_f64(), Some(42.0));
+}
+
+#[test]
+fn evaluate_returns_traced_errors() {
+    let mut interpreter = Interpreter::builder().file("main.thor").build();
+
+    let error = interpreter
+        .evaluate("fn fail(){ throw \"no\"; }\nfail();")
+        .unwrap_err();
+
+    let ThorLangError::Traced {
+        error: inner,
+        source,
+        trace,
+    } = &error
+    else {
+        panic!("expected a traced error but got {error:?}");
+    };
+
+    assert_eq!(inner.code(), error.code());
+    assert_eq!(
+        source.as_ref().and_then(|source| source.name.as_deref()),
+        Some("main.thor")
+    );
+    assert_eq!(trace.len(), 1);
+    assert_eq!(trace[0].function, "fail");
+}
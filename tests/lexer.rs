@@ -0,0 +1,144 @@
+//the positions of the tokens, lines and columns start at 1 and count characters, offsets and
+//lengths are in bytes
+use execution_lib::lexer;
+use type_lib::{ThorLangError, TokenType};
+
+//line, column, offset and length of every token
+fn spans(text: &str) -> Vec<(i32, i32, usize, usize)> {
+    lexer(text.to_string())
+        .unwrap()
+        .iter()
+        .map(|token| (token.line, token.column, token.offset, token.length))
+        .collect()
+}
+
+//the text every token covers
+fn lexemes(text: &str) -> Vec<&str> {
+    lexer(text.to_string())
+        .unwrap()
+        .iter()
+        .map(|token| &text[token.offset..token.offset + token.length])
+        .collect()
+}
+
+#[test]
+fn tokens_know_their_position() {
+    assert_eq!(
+        spans("let a = 10;\nprint a;"),
+        vec![
+            (1, 1, 0, 3),
+            (1, 5, 4, 1),
+            (1, 7, 6, 1),
+            (1, 9, 8, 2),
+            (1, 11, 10, 1),
+            (2, 1, 12, 5),
+            (2, 7, 18, 1),
+            (2, 8, 19, 1),
+            (2, 0, 20, 0),
+        ]
+    );
+}
+
+#[test]
+fn multi_byte_characters_count_as_one_column() {
+    let text = "let s = \"héllo→\"; print s;";
+
+    assert_eq!(lexemes(text)[3], "\"héllo→\"");
+    assert_eq!(spans(text)[3..5], [(1, 9, 8, 11), (1, 17, 19, 1)]);
+    assert_eq!(spans(text)[5], (1, 19, 21, 5));
+}
+
+#[test]
+fn tokens_after_multi_line_strings_are_on_the_right_line() {
+    let text = "print \"a\nb\";\nlet x;";
+    let spans = spans(text);
+
+    //the string starts on the first line and goes over two
+    assert_eq!(spans[1], (1, 7, 6, 5));
+    assert_eq!(spans[2], (2, 3, 11, 1));
+    assert_eq!(spans[3], (3, 1, 13, 3));
+}
+
+#[test]
+fn comments_and_whitespace_are_skipped() {
+    let text = "let a = 1; //a comment\n\t  a;";
+
+    assert_eq!(lexemes(text), vec!["let", "a", "=", "1", ";", "a", ";", ""]);
+    assert_eq!(spans(text)[5], (2, 4, 26, 1));
+}
+
+#[test]
+fn token_types() {
+    let types: Vec<TokenType> = lexer("fn f(x) { return x >= 1.5; }".to_string())
+        .unwrap()
+        .into_iter()
+        .map(|token| token.token_type)
+        .collect();
+
+    assert_eq!(types[0], TokenType::FN);
+    assert_eq!(types[1], TokenType::IDENTIFIER("f".to_string()));
+    assert_eq!(types[8], TokenType::GREATEREQ);
+    assert_eq!(types[9], TokenType::NUMBER("1.5".to_string()));
+    assert_eq!(types.last(), Some(&TokenType::EOF));
+}
+
+//the value of the only string in the text
+fn string(text: &str) -> String {
+    match &lexer(text.to_string()).unwrap()[0].token_type {
+        TokenType::STRING(string) => string.clone(),
+        token_type => panic!("expected a string but got {token_type:?}"),
+    }
+}
+
+#[test]
+fn escapes() {
+    assert_eq!(string(r#""a\tb\nc""#), "a\tb\nc");
+    assert_eq!(string(r#""\\ \"quoted\"""#), "\\ \"quoted\"");
+    assert_eq!(string(r#""\u{2603}\u{1F600}""#), "\u{2603}\u{1F600}");
+}
+
+#[test]
+fn triple_quoted_strings() {
+    //the newline after the opening quotes is left out, quotes don't need escapes
+    assert_eq!(
+        string("\"\"\"\nshe said \"hi\"\nand left\"\"\""),
+        "she said \"hi\"\nand left"
+    );
+    assert_eq!(string(r#""""tab\t""""#), "tab\t");
+
+    let tokens = lexer("\"\"\"a\nb\"\"\" x".to_string()).unwrap();
+    assert_eq!((tokens[0].offset, tokens[0].length), (0, 9));
+    assert_eq!((tokens[1].line, tokens[1].column), (2, 6));
+}
+
+#[test]
+fn invalid_escapes_point_at_the_backslash() {
+    let error = lexer("let s = \"ok\";\nprint \"é \\q\";".to_string()).unwrap_err();
+
+    assert_eq!(
+        error,
+        ThorLangError::InvalidEscape {
+            escape: "\\q".to_string(),
+            line: 2,
+            column: 10,
+        }
+    );
+
+    let error = lexer(r#""\u{110000}""#.to_string()).unwrap_err();
+    assert!(matches!(
+        error,
+        ThorLangError::InvalidEscape { column: 2, .. }
+    ));
+}
+
+#[test]
+fn unterminated_strings_point_at_their_start() {
+    assert_eq!(
+        lexer("print 1;\n  \"open".to_string()).unwrap_err(),
+        ThorLangError::UnterminatedString { line: 2, column: 3 }
+    );
+    assert_eq!(
+        lexer(r#""""a"""#.to_string()).unwrap_err(),
+        ThorLangError::UnterminatedString { line: 1, column: 1 }
+    );
+}
@@ -0,0 +1,137 @@
+//every interpreter has its own natives
+use execution_lib::Interpreter;
+use type_lib::{EnvState, Environment, RegisteredFnMap, ThorLangError, Value};
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+fn answer(
+    _: HashMap<String, Value>,
+    _: Option<Value>,
+    _: Option<Arc<Mutex<Environment>>>,
+    _: Option<EnvState>,
+) -> Result<Value, ThorLangError> {
+    Ok(Value::number(42.0))
+}
+
+#[test]
+fn interpreters_have_their_own_natives() {
+    let mut with_native = Interpreter::builder()
+        .native(Value::simple_function("answer", vec![]), Arc::new(answer))
+        .unwrap()
+        .build();
+    let mut without_native = Interpreter::builder().build();
+
+    assert!(!Arc::ptr_eq(
+        &with_native.registry(),
+        &without_native.registry()
+    ));
+
+    assert_eq!(with_native.run("answer();").unwrap().to_f64(), Some(42.0));
+    assert!(without_native.run("answer();").is_err());
+}
+
+#[test]
+fn natives_of_the_embedder_replace_built_in_ones() {
+    let mut interpreter = Interpreter::builder()
+        .native(
+            Value::simple_function("stringify", vec!["value"]),
+            Arc::new(answer),
+        )
+        .unwrap()
+        .build();
+    let mut other = Interpreter::builder().build();
+
+    assert_eq!(
+        interpreter.run("stringify(1);").unwrap().to_f64(),
+        Some(42.0)
+    );
+    assert_eq!(other.run("stringify(1);").unwrap().to_f64(), None);
+}
+
+#[test]
+fn registering_a_body_again_replaces_it() {
+    let registry = RegisteredFnMap::default();
+    let function = Value::simple_function("answer", vec![]);
+
+    function.register_function_body(&registry, Arc::new(|_, _, _, _| Ok(Value::number(1.0))));
+    function.register_function_body(&registry, Arc::new(answer));
+
+    let body = registry.lock().unwrap().functions["answer"].clone();
+
+    assert_eq!(
+        body(HashMap::new(), None, None, None).unwrap().to_f64(),
+        Some(42.0)
+    );
+}
+
+#[test]
+fn imported_modules_keep_the_natives_of_the_embedder() {
+    let dir = std::env::temp_dir().join(format!("thorlang-registry-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("module.thor"), "stringify(1);").unwrap();
+
+    let mut interpreter = Interpreter::builder()
+        .env_state(EnvState::new(dir.clone()))
+        .native(
+            Value::simple_function("stringify", vec!["value"]),
+            Arc::new(answer),
+        )
+        .unwrap()
+        .build();
+
+    let value = interpreter.run("import(\"module.thor\");");
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    //the module doesn't register the built in stringify again
+    assert_eq!(value.unwrap().to_f64(), Some(42.0));
+}
+
+#[test]
+fn host_closures_can_be_called_from_scripts() {
+    let calls = Arc::new(Mutex::new(vec![]));
+    let tracked = calls.clone();
+
+    let mut interpreter = Interpreter::builder()
+        .function("track", 2, move |arguments| {
+            tracked.lock().unwrap().push(arguments[0].to_f64());
+            Ok(arguments[1].clone())
+        })
+        .build();
+
+    let value = interpreter.run("track(1, nil); track(2, 3);").unwrap();
+
+    assert_eq!(value.to_f64(), Some(3.0));
+    assert_eq!(*calls.lock().unwrap(), vec![Some(1.0), Some(2.0)]);
+
+    //the arity is checked before the closure is called
+    assert!(interpreter.run("track(1);").is_err());
+    assert_eq!(calls.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn host_functions_dont_replace_methods() {
+    let mut interpreter = Interpreter::builder()
+        .function("len", 1, |_| Ok(Value::number(99.0)))
+        .function("floor", 1, |_| Ok(Value::number(98.0)))
+        .build();
+
+    assert_eq!(
+        interpreter.run("[1, 2, 3].len();").unwrap().to_f64(),
+        Some(3.0)
+    );
+    assert_eq!(
+        interpreter.run("(2.5).floor();").unwrap().to_f64(),
+        Some(2.0)
+    );
+    assert_eq!(interpreter.run("len([]);").unwrap().to_f64(), Some(99.0));
+}
+
+#[test]
+fn natives_have_to_be_named_functions() {
+    let native = Interpreter::builder().native(Value::number(1.0), Arc::new(answer));
+
+    assert!(matches!(native, Err(ThorLangError::RuntimeError { .. })));
+}
@@ -0,0 +1,85 @@
+//runs the regression scripts in thor_examples and compares what they print with the expected output
+//in tests/expected, the scripts throw as soon as one of their checks fails
+use execution_lib::Interpreter;
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+fn run_script(name: &str) {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let file = format!("{name}.thor");
+
+    let code = fs::read_to_string(root.join("thor_examples").join(&file)).unwrap();
+    let expected =
+        fs::read_to_string(root.join("tests/expected").join(format!("{name}.out"))).unwrap();
+
+    let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+
+    let mut interpreter = Interpreter::builder().output(output.clone()).build();
+
+    if let Err(diagnostic) = interpreter.run(&code) {
+        panic!("{file} failed:\n{diagnostic}");
+    }
+
+    let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+    assert_eq!(
+        output, expected,
+        "{file} printed something else than expected"
+    );
+}
+
+#[test]
+fn closures() {
+    run_script("closures");
+}
+
+#[test]
+fn interpolation() {
+    run_script("interpolation");
+}
+
+#[test]
+fn logic() {
+    run_script("logic");
+}
+
+#[test]
+fn operators() {
+    run_script("operators");
+}
+
+#[test]
+fn conditionals() {
+    run_script("conditionals");
+}
+
+#[test]
+fn match_expressions() {
+    run_script("match");
+}
+
+#[test]
+fn destructuring() {
+    run_script("destructuring");
+}
+
+#[test]
+fn parameters() {
+    run_script("parameters");
+}
+
+#[test]
+fn errors() {
+    run_script("errors");
+}
+
+#[test]
+fn references() {
+    run_script("references");
+}
+
+#[test]
+fn comments() {
+    run_script("comments");
+}
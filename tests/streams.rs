@@ -0,0 +1,52 @@
+//print and get_input go through the streams given to the interpreter
+use execution_lib::Interpreter;
+
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+fn captured(output: &Arc<Mutex<Vec<u8>>>) -> String {
+    String::from_utf8(output.lock().unwrap().clone()).unwrap()
+}
+
+#[test]
+fn print_writes_to_the_output_stream() {
+    let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+
+    let mut interpreter = Interpreter::builder().output(output.clone()).build();
+    interpreter.run("print \"hello\"; print 1 + 2;").unwrap();
+
+    assert_eq!(captured(&output), "hello\n3\n");
+}
+
+#[test]
+fn get_input_reads_from_the_input_stream() {
+    let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+    let input = Arc::new(Mutex::new(Cursor::new(b"thor\nloki\n".to_vec())));
+
+    let mut interpreter = Interpreter::builder()
+        .output(output.clone())
+        .input(input)
+        .build();
+
+    interpreter
+        .run("let first = get_input(\"name?\"); let second = get_input(nil); print first + second;")
+        .unwrap();
+
+    //the message is written to the output before reading
+    assert_eq!(captured(&output), "name?\nthorloki\n");
+}
+
+#[test]
+fn interpreters_write_to_their_own_streams() {
+    let first = Arc::new(Mutex::new(Vec::<u8>::new()));
+    let second = Arc::new(Mutex::new(Vec::<u8>::new()));
+
+    let mut a = Interpreter::builder().output(first.clone()).build();
+    let mut b = Interpreter::builder().output(second.clone()).build();
+
+    a.run("print \"a\";").unwrap();
+    b.run("print \"b\";").unwrap();
+
+    assert_eq!(captured(&first), "a\n");
+    assert_eq!(captured(&second), "b\n");
+}
@@ -1,20 +1,74 @@
 use std::collections::HashMap;
-use std::cell::RefCell;
-use std::sync:: {Arc, Mutex};
-use std::rc::Rc;
+use std::sync:: {Arc, Mutex, MutexGuard};
 
 use std::fmt;
+use std::io::{self, BufRead, Write};
 
 use libloading::Library;
 
 
 use std::path::PathBuf;
 
+//where the output of print goes and where get_input reads from
+pub type OutputStream = Arc<Mutex<dyn Write + Send>>;
+pub type InputStream = Arc<Mutex<dyn BufRead + Send>>;
+
 //structure to get executable information later (for now it only serves so we can get the current
-//execution directory)
-#[derive(Clone, Debug)]
+//execution directory and the streams scripts read from and write to)
+#[derive(Clone, Default)]
 pub struct EnvState {
     pub path: PathBuf,
+    //when no streams are given stdout and stdin of the process are used
+    pub output: Option<OutputStream>,
+    pub input: Option<InputStream>,
+}
+
+impl EnvState {
+    pub fn new(path: PathBuf) -> Self {
+        EnvState {
+            path,
+            ..Default::default()
+        }
+    }
+
+    //writes a line to the output stream
+    pub fn write_line(&self, text: &str) -> Result<(), ThorLangError> {
+        let written = match &self.output {
+            Some(output) => writeln!(output.lock().unwrap(), "{text}"),
+            None => writeln!(io::stdout(), "{text}"),
+        };
+
+        written.map_err(|err| ThorLangError::RuntimeError {
+            message: format!("could not write output: {err}"),
+        })
+    }
+
+    //reads a line from the input stream, the line break is not part of the returned string
+    pub fn read_line(&self) -> Result<String, ThorLangError> {
+        let mut line = String::new();
+
+        let read = match &self.input {
+            Some(input) => input.lock().unwrap().read_line(&mut line),
+            None => io::stdin().lock().read_line(&mut line),
+        };
+
+        match read {
+            Ok(_) => Ok(line.trim_end_matches(['\n', '\r']).to_string()),
+            Err(err) => Err(ThorLangError::RuntimeError {
+                message: format!("could not read input: {err}"),
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for EnvState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnvState")
+            .field("path", &self.path)
+            .field("output", &self.output.is_some())
+            .field("input", &self.input.is_some())
+            .finish()
+    }
 }
 
 //the different token types
@@ -33,7 +87,13 @@ pub enum TokenType {
     SEMICOLON,
     SLASH,
     STAR,
+    PERCENT,
+    //~/ divides and rounds down (// is already taken by comments)
+    TILDESLASH,
+    STARSTAR,
     COLON,
+    //=> between the pattern and the value of a match arm
+    FATARROW,
     TO, //array initializer
     ON,
     STEP,
@@ -47,16 +107,30 @@ pub enum TokenType {
     LESS,
     LESSEQ,
 
+    //compound assignments (a += b is a = a + b)
+    PLUSEQ,
+    MINUSEQ,
+    STAREQ,
+    SLASHEQ,
+    PERCENTEQ,
+
     IDENTIFIER(String),
     STRING(String),
+    //the part of an interpolated string before a ${, the tokens of the expression and an
+    //INTERPOLATIONEND follow it, the string ends with a normal STRING token
+    STRINGPART(String),
+    //the } that closes a ${, it has its own type so it is never mistaken for the end of a block
+    INTERPOLATIONEND,
     NUMBER(String),
 
     SPECIAL(String),
 
     TRY,
+    MATCH,
     OVERLOAD,
     DO,
     AND,
+    OR,
     ELSE,
     FALSE,
     FN,
@@ -70,6 +144,8 @@ pub enum TokenType {
     THROW,
     FOR,
     IN,
+    BREAK,
+    CONTINUE,
 
     EOF,
 }
@@ -82,6 +158,7 @@ impl TokenType {
         match &self{
             TokenType::NUMBER(num) => Some(num.to_string()),
             TokenType::STRING(str) => Some(str.to_string()),
+            TokenType::STRINGPART(str) => Some(str.to_string()),
             TokenType::IDENTIFIER(id) => Some(id.to_string()),
             _ => None
         }
@@ -95,7 +172,11 @@ impl TokenType {
 pub struct Token {
     pub token_type: TokenType,
     pub line: i32,
-    pub column : i32
+    pub column : i32,
+    //where the token starts in the text and how long it is (both in bytes), tokens can span
+    //multiple lines so line and column only tell where they start
+    pub offset : usize,
+    pub length : usize
 }
 
 //statements
@@ -118,7 +199,21 @@ pub enum Statement {
     Variable {
         name: String,
         expression: Expression,
-        line : i32
+        line : i32,
+        name_token_index : usize,
+        //the slot in the current scope, set by the resolver (none for global variables)
+        slot : Option<usize>
+    },
+    //let [a, b] = value; and let {"x": x} = value; (for loops over patterns use it as well)
+    Destructure {
+        pattern : Pattern,
+        expression : Expression,
+        line : i32,
+        //the = (or the in of a for loop), errors about values that don't fit the pattern point at it
+        token_index : usize,
+        //the slots of the names the pattern binds in the order of Pattern::bindings, set by the
+        //resolver (none for global variables)
+        slots : Vec<Option<usize>>
     },
     Block {
         statements: Vec<Statement>,
@@ -144,12 +239,29 @@ pub enum Statement {
         name: String,
         body: Box<Vec<Statement>>,
         arguments: Vec<String>,
-        line : i32
+        arity : Arity,
+        line : i32,
+        //the slot in the current scope, set by the resolver (none for global functions)
+        slot : Option<usize>
     },
     Return {
         expression: Expression,
         line : i32
     },
+    //the parser puts one at the start of the body of a function for every parameter with a
+    //default, it only runs when the argument in the slot at index was left out
+    DefaultArgument {
+        name : String,
+        index : usize,
+        default : Expression
+    },
+    //only allowed inside of while and for loops
+    Break {
+        break_token_index : usize
+    },
+    Continue {
+        continue_token_index : usize
+    },
     Overload {
         operator : TokenType, 
         operands : Vec<String>,
@@ -159,6 +271,77 @@ pub enum Statement {
 }
 
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern : Pattern,
+    pub guard : Option<Expression>,
+    pub body : Expression
+}
+
+//the patterns of match arms, the names bound by a pattern live in an environment of their own that
+//only the guard and the body of the arm can see
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    //_ matches everything and binds nothing
+    Wildcard,
+    //a name matches everything and binds the value to it
+    Binding {
+        name : String,
+        name_token_index : usize
+    },
+    //numbers, strings, bools and nil match values that are equal to them
+    Literal {
+        literal : TokenType,
+        literal_token_index : usize
+    },
+    //one of the names type_of returns (like number or array), optionally followed by a pattern for
+    //the value (number n)
+    Type {
+        type_name : String,
+        binding : Option<Box<Pattern>>
+    },
+    //arrays with exactly as many elements as there are patterns, or at least as many when there is
+    //a rest (..rest), the rest gets the remaining elements as a new array
+    Array {
+        elements : Vec<Pattern>,
+        rest : Option<Box<Pattern>>
+    },
+    //objects that have all of the keys (and maybe more) with values matching the patterns
+    Object {
+        keys : Vec<String>,
+        values : Vec<Pattern>
+    },
+    //an element or field of a destructuring let (or for) with the value it gets when it is missing
+    //(let [a, b = nil] = arr;), only the patterns of let and for can have defaults
+    Default {
+        pattern : Box<Pattern>,
+        default : Box<Expression>
+    }
+}
+
+impl Pattern {
+    //the names a pattern binds (with the index of their token) in the order they are matched, this
+    //order gives every name its slot in the environment of the arm
+    pub fn bindings(&self) -> Vec<(String, usize)> {
+        match self {
+            Pattern::Binding { name, name_token_index } => vec![(name.to_string(), *name_token_index)],
+            Pattern::Wildcard | Pattern::Literal { .. } => vec![],
+            Pattern::Type { binding, .. } => binding.iter().flat_map(|binding| binding.bindings()).collect(),
+            Pattern::Array { elements, rest } => elements.iter().chain(rest.as_deref()).flat_map(|element| element.bindings()).collect(),
+            Pattern::Object { values, .. } => values.iter().flat_map(|value| value.bindings()).collect(),
+            Pattern::Default { pattern, .. } => pattern.bindings(),
+        }
+    }
+}
+
+//where a variable lives after the resolver pass: how many environments up the env tree it is and
+//its index in the slots of that environment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slot {
+    pub depth : usize,
+    pub index : usize
+}
+
 //unlike parser errors we know that the tokenlist works in here and we can point to the token that
 //has an error 
 //this means it sufficces to just put in the index to the wanted token
@@ -167,6 +350,20 @@ pub enum Expression {
     Try {
         block : Vec<Statement>
     },
+    //if in expression position, its value is the value of the last expression in the branch that
+    //runs (nil when there is none)
+    If {
+        condition : Box<Expression>,
+        then_branch : Vec<Statement>,
+        else_branch : Option<Vec<Statement>>
+    },
+    //the value is compared against the patterns of the arms from top to bottom, the first arm
+    //that matches (and whose guard is true) gives the value of the match
+    Match {
+        value : Box<Expression>,
+        arms : Vec<MatchArm>,
+        match_token_index : usize
+    },
     On{
         block : Option<Vec<Statement>>,
         variables : Vec<Expression>,
@@ -174,11 +371,14 @@ pub enum Expression {
     },
     Lambda{
         block : Vec<Statement>, 
-        arguments : Vec<String>
+        arguments : Vec<String>,
+        arity : Arity
     },
     Identifier {
         name: String,
-        identifier_token_index : usize
+        identifier_token_index : usize,
+        //where the variable lives, set by the resolver (none when it is looked up by name)
+        slot : Option<Slot>
     },
     Binary {
         left: Box<Expression>,
@@ -186,6 +386,13 @@ pub enum Expression {
         right: Box<Expression>,
         operator_token_index : usize
     },
+    //and / or, the right side is only evaluated when the left side does not decide the result
+    Logical {
+        left: Box<Expression>,
+        operator: TokenType,
+        right: Box<Expression>,
+        operator_token_index : usize
+    },
     Unary {
         operator: TokenType,
         right: Box<Expression>,
@@ -201,7 +408,9 @@ pub enum Expression {
     Assignment {
         target: Box<Expression>,
         value: Box<Expression>,
-        eq_token_index : usize
+        eq_token_index : usize,
+        //the operator of a compound assignment (PLUS for +=), none for =
+        operator : Option<TokenType>
     },
     Array {
         values: Vec<Expression>,
@@ -210,10 +419,17 @@ pub enum Expression {
         keys : Vec<Expression>,
         values : Vec<Expression>
     },
+    //"a ${b} c", the string parts are literals and everything is joined into one string
+    Interpolation {
+        parts : Vec<Expression>,
+        string_token_index : usize
+    },
     Call {
         callee: Box<Expression>,
         paren_token_index: usize,
         arguments: Vec<Expression>,
+        //the names of the last arguments when they are passed by name (f(1, c: 3))
+        names: Vec<String>,
     },
     Retrieve {
         retrievee: Box<Expression>,
@@ -228,14 +444,246 @@ pub enum Expression {
 }
 
 
+//bytecode
+
+//the ast is compiled into a list of operations that are executed by the vm with a value stack, every
+//operation takes its operands from the top of the stack and pushes its result back onto it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    //pushes a (pre evaluated) literal
+    Constant(Value),
+    //variables without a slot are looked up by name
+    Get {
+        name : String,
+        slot : Option<Slot>,
+        identifier_token_index : usize
+    },
+    //pops the value and declares a variable with it in the current scope
+    Define {
+        name : String,
+        slot : Option<usize>
+    },
+    //pops the computed keys of the steps and then the value, the assigned value is pushed again.
+    //compound assignments combine the current value of the target with the popped one first
+    Assign {
+        name : String,
+        slot : Option<Slot>,
+        steps : Vec<AssignStep>,
+        eq_token_index : usize,
+        operator : Option<TokenType>
+    },
+    Pop,
+    Unary {
+        operator : TokenType,
+        operator_token_index : usize
+    },
+    Binary {
+        operator : TokenType,
+        operator_token_index : usize
+    },
+    //checks that the top of the stack is a bool, when it decides the result of the and / or (false
+    //for and, true for or) it is left on the stack and the vm jumps to end, else it is popped
+    Logical {
+        operator : TokenType,
+        end : usize,
+        operator_token_index : usize
+    },
+    //pops the given number of elements
+    Array(usize),
+    //pops the given number of key value pairs
+    Object(usize),
+    //pops the given number of parts and joins them into a string
+    Interpolate {
+        parts : usize,
+        string_token_index : usize
+    },
+    //pops the retrievee and then the key
+    Retrieve(usize),
+    //when there is no key name it is computed and lies on the stack above the callee
+    Field {
+        key : Option<String>,
+        dot_token_index : usize
+    },
+    //pops the arguments and then the callee, the last arguments are passed by the names
+    Call {
+        arguments : usize,
+        names : Vec<String>,
+        paren_token_index : usize
+    },
+    //declares a named function in the current scope
+    Function {
+        function : Arc<FunctionProto>,
+        slot : Option<usize>
+    },
+    Lambda(Arc<FunctionProto>),
+    //opens a try block, an error inside of it (or in anything it calls) jumps to the given position
+    //with the error as the value
+    TryStart(usize),
+    //closes the innermost try block, its value is kept on the stack
+    TryEnd,
+    //closes the innermost try block without a value (break and continue leaving it)
+    LeaveTry,
+    //pops the value and adds the listener to it (and to the variable when there is a name)
+    Listen {
+        name : Option<String>,
+        slot : Option<Slot>,
+        listener : Arc<Chunk>,
+        on_token_index : usize
+    },
+    Overload {
+        operator : TokenType,
+        operands : Vec<String>,
+        operation : Arc<Chunk>
+    },
+    //tests the value on top of the stack against the pattern, when it matches the names it binds
+    //are declared in a new scope, else the vm jumps to next
+    Match {
+        pattern : Pattern,
+        names : Vec<String>,
+        next : usize
+    },
+    //pushes the element or field of the value on top of the stack (which stays there), when it is
+    //missing the vm jumps to the code of the default or raises an error if there is none
+    Destructure {
+        key : DestructureKey,
+        default : Option<usize>,
+        token_index : usize
+    },
+    //raises the error for a match where no arm matched the value on top of the stack
+    NoMatch(usize),
+    //swaps the two values on top of the stack
+    Swap,
+    //jumps to skip when the argument in the slot at index of the current scope was passed (so its
+    //default is not evaluated)
+    SkipDefault {
+        index : usize,
+        skip : usize
+    },
+    //pops the return value and leaves the current function (or try block)
+    Return,
+    Print,
+    Throw(usize),
+    EvalError(usize),
+    PushScope,
+    PopScope,
+    Jump(usize),
+    //pops the condition, true continues, false jumps to on_false and anything else to otherwise
+    Branch {
+        on_false : usize,
+        otherwise : usize
+    },
+    //expects the array and the current index on the stack, declares the variable with the next
+    //element in a new scope (as its first slot) or pops both and jumps to exit when there is none
+    //left
+    Iterate {
+        variable : String,
+        exit : usize
+    },
+}
+
+//the part of a value that a step of a destructuring takes out
+#[derive(Debug, Clone, PartialEq)]
+pub enum DestructureKey {
+    Index(usize),
+    //the elements from the index on, as a new array
+    Rest(usize),
+    Field(String),
+}
+
+//the steps of an assignment target after the variable name (obj.hello[0] has two steps)
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssignStep {
+    //a field name and the index of the dot in front of it
+    Field(String, usize),
+    //a key that is computed at runtime and the index of the bracket
+    Key(usize),
+    //a field name that is computed at runtime (and hashed) and the index of the dot
+    Hashed(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Chunk {
+    pub code : Vec<Op>,
+    //the code the chunk was compiled from, the token indices in its operations point into its tokens
+    //(none for code that is compiled at runtime)
+    pub source : Option<Arc<Source>>,
+}
+
+//a piece of code together with the tokens it was lexed into and the file it was read from (none for
+//the repl and embedded code)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Source {
+    pub name : Option<String>,
+    pub text : String,
+    pub tokens : Vec<Token>,
+}
+
+//a call an error passed through on its way out, call_token_index is the ( of the call in the tokens
+//of source (calls made by natives, like listeners, have none)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceFrame {
+    pub function : String,
+    pub call_token_index : Option<usize>,
+    pub source : Option<Arc<Source>>,
+}
+
+//everything needed to create a thorfunction at runtime
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionProto {
+    pub name : String,
+    pub arguments : Vec<String>,
+    pub arity : Arity,
+    pub body : Arc<Chunk>,
+}
+
+//how many arguments a function accepts, parameters with a default can be left out and a rest
+//parameter (...items) takes any number of them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arity {
+    pub min : usize,
+    //none when there is a rest parameter
+    pub max : Option<usize>
+}
+
+impl Arity {
+    pub fn exactly(count : usize) -> Self {
+        Arity { min : count, max : Some(count) }
+    }
+
+    pub fn accepts(&self, count : usize) -> bool {
+        count >= self.min && self.max.map_or(true, |max| count <= max)
+    }
+}
+
+//2, 1 to 3 or at least 1
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{} to {}", self.min, max),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
+}
+
+
 //this is a bit more complicated, Rc<Refcell<T>> provides us with the ability to mutate the entire
 //environment object at will (its just some trickery so we can do that) terrible performance
 //decision, but makes it wor
 #[derive(Debug, Clone)]
 pub struct Environment {
+    //values that are declared by name (globals and code run by eval)
     pub values: Arc<Mutex<HashMap<String, Value>>>,
+    //values of variables the resolver gave a slot, a slot is none until its declaration ran
+    pub slots: Vec<Option<Value>>,
+    //the names of the slots, so natives can still find them by name
+    pub names: Vec<String>,
     pub enclosing: Option<Arc<Mutex<Environment>>>,
-    pub overloadings : Overloadings
+    pub overloadings : Overloadings,
+    //only the root environment of an interpreter holds the registry and the env_state, every other
+    //environment asks its parent
+    pub registry : Option<RegisteredFnMap>,
+    pub env_state : Option<EnvState>
 }
 
 //its easier to instantiate a get and set function that automatically search the entire env tree
@@ -245,8 +693,12 @@ impl Environment {
     pub fn new(enclosing: Option<Arc<Mutex<Environment>>>) -> Arc<Mutex<Self>> {
         Arc::new(Mutex::new(Environment {
             values: Arc::new(Mutex::new(HashMap::new())),
+            slots : Vec::new(),
+            names : Vec::new(),
             enclosing, 
-            overloadings : HashMap::new()
+            overloadings : HashMap::new(),
+            registry : None,
+            env_state : None
         }))
     }
    
@@ -258,6 +710,10 @@ impl Environment {
         if let Some(value) = self.values.lock().unwrap().get(key) {
             Some(value.clone())
         } 
+        //variables with a slot can be found by their name as well
+        else if let Some(index) = self.slot_of(key) {
+            self.slots[index].clone()
+        }
         //if not we return a reference to the environment that closes over the current one and
         //apply this "get" method to it
         else if let Some(ref parent) = self.enclosing {
@@ -273,7 +729,7 @@ impl Environment {
     //almost the same as with get but we have to change the .borrow() (normal reference) to a
     //.borrow_mut() (mutable reference) since we want to be able to change whatever value we
     //encounter
-    pub fn set(&self, key: String, value: Value, eq_token_index : usize) -> Result<Value, ThorLangError> {
+    pub fn set(&mut self, key: String, value: Value, eq_token_index : usize) -> Result<Value, ThorLangError> {
 
         if self.values.lock().unwrap().contains_key(&key) {
             //we need to reassign the listeners to the new value
@@ -285,6 +741,8 @@ impl Environment {
 
 
             Ok(p.unwrap())
+        } else if let Some(index) = self.slot_of(&key) {
+            self.set_at(0, index, value, eq_token_index)
         } else if let Some(ref parent) = self.enclosing {
             parent.lock().unwrap().set(key, value, eq_token_index)
         } else {
@@ -298,6 +756,58 @@ impl Environment {
         }
     }
 
+    //the index of the (declared) slot with the given name
+    fn slot_of(&self, key : &str) -> Option<usize> {
+        self.names.iter().enumerate().rev().find(|(index, name)| {
+            *name == key && self.slots[*index].is_some()
+        }).map(|(index, _)| index)
+    }
+
+    //returns the value in the slot of the environment "depth" levels up, none when the slot was
+    //not declared yet
+    pub fn get_at(&self, depth : usize, index : usize) -> Option<Value> {
+        if depth == 0 {
+            self.slots.get(index).cloned().flatten()
+        } else if let Some(ref parent) = self.enclosing {
+            parent.lock().unwrap().get_at(depth - 1, index)
+        } else {
+            None
+        }
+    }
+
+    //like set but for slots, the old value is returned
+    pub fn set_at(&mut self, depth : usize, index : usize, value : Value, eq_token_index : usize) -> Result<Value, ThorLangError> {
+        if depth > 0 {
+            return match self.enclosing {
+                Some(ref parent) => parent.lock().unwrap().set_at(depth - 1, index, value, eq_token_index),
+                None => ThorLangError::eval_error(eq_token_index)
+            };
+        }
+
+        match self.slots.get_mut(index) {
+            Some(Some(old_value)) => {
+                //we need to reassign the listeners to the new value
+                let mut new_val = value;
+                new_val.listeners = old_value.listeners.clone();
+
+                Ok(std::mem::replace(old_value, new_val))
+            },
+            //assigning to a variable before its declaration ran
+            _ => ThorLangError::eval_error(eq_token_index)
+        }
+    }
+
+    //declares the variable in the given slot of this environment
+    pub fn define_at(&mut self, index : usize, name : &str, value : Value) {
+        if self.slots.len() <= index {
+            self.slots.resize(index + 1, None);
+            self.names.resize(index + 1, String::new());
+        }
+
+        self.slots[index] = Some(value);
+        self.names[index] = name.to_string();
+    }
+
     pub fn set_overloadings(&mut self, overloadings : Overloadings){
        
 
@@ -325,18 +835,44 @@ impl Environment {
     }
 
 
-    pub fn add_listener(&self, key : String, listener : Vec<Statement>, on_token_index : usize) -> Result<Value, ThorLangError>{
+    //moves up to the root of the env tree to find the registry of the interpreter this environment
+    //belongs to
+    pub fn get_registry(&self) -> Option<RegisteredFnMap>{
+        if let Some(registry) = &self.registry{
+            Some(registry.clone())
+        }else if let Some(parent_env) = &self.enclosing{
+            parent_env.lock().unwrap().get_registry()
+        }else{
+            None
+        }
+    }
+
+    //same as with the registry, the env_state (and with it the io streams) is stored at the root
+    pub fn get_env_state(&self) -> Option<EnvState>{
+        if let Some(env_state) = &self.env_state{
+            Some(env_state.clone())
+        }else if let Some(parent_env) = &self.enclosing{
+            parent_env.lock().unwrap().get_env_state()
+        }else{
+            None
+        }
+    }
 
+    //listeners are functions without arguments that run whenever the variable is assigned to
+    pub fn add_listener(&mut self, key : String, listener : Value, on_token_index : usize) -> Result<Value, ThorLangError>{
 
-        if let Some(value) = self.values.lock().unwrap().get_mut(&key){
-            match &mut value.listeners {
-                Some(listeners) => {
-                    listeners.push(listener) 
-                },
-                None => {
-                    value.listeners = Some(vec![listener])
-                }
-            } 
+        let mut values = self.values.lock().unwrap();
+
+        let value = match values.get_mut(&key) {
+            Some(value) => Some(value),
+            None => match self.names.iter().rposition(|name| *name == key) {
+                Some(index) => self.slots[index].as_mut(),
+                None => None
+            }
+        };
+
+        if let Some(value) = value {
+            value.listeners.get_or_insert_with(Vec::new).push(listener);
             return Ok(Value::nil())
         } else if let Some(ref parent) = self.enclosing {
             parent.lock().unwrap().add_listener(key, listener, on_token_index)?;
@@ -346,13 +882,33 @@ impl Environment {
         ThorLangError::eval_error(on_token_index)
     }
 
+    //same as add_listener for a variable with a slot
+    pub fn add_listener_at(&mut self, depth : usize, index : usize, listener : Value, on_token_index : usize) -> Result<Value, ThorLangError>{
+        if depth > 0 {
+            return match self.enclosing {
+                Some(ref parent) => parent.lock().unwrap().add_listener_at(depth - 1, index, listener, on_token_index),
+                None => ThorLangError::eval_error(on_token_index)
+            };
+        }
+
+        match self.slots.get_mut(index) {
+            Some(Some(value)) => {
+                value.listeners.get_or_insert_with(Vec::new).push(listener);
+                Ok(Value::nil())
+            },
+            _ => ThorLangError::eval_error(on_token_index)
+        }
+    }
+
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct OperationInfo {
     pub operands: Vec<String>,
-    pub operation: Vec<Statement>,
+    pub operation: Arc<Chunk>,
     pub overloadings: Overloadings,
+    //the environment the overloading was defined in, like the closure of a function
+    pub closure: Arc<Mutex<Environment>>,
 }
 
 //Hashmap that returns a operation given an operator (TokenType) and an arity (usize)
@@ -360,10 +916,63 @@ pub type Overloadings = HashMap<(TokenType, usize), Vec<OperationInfo>>;
 
 
 //these functions are the ones actually loaded in
-//arguments, self_value, environment, variable name, env_state
-pub type FnType = Arc<fn(HashMap<String, Value>, Option<Value>, Option<Arc<Mutex<Environment>>>, Option<String>, Option<EnvState>) -> Result<Value, ThorLangError>>;
+//arguments, self_value, environment, env_state
+//
+//any closure works, so natives can capture state of the host (handles, counters, channels...)
+pub type FnType = Arc<dyn Fn(HashMap<String, Value>, Option<Value>, Option<Arc<Mutex<Environment>>>, Option<EnvState>) -> Result<Value, ThorLangError> + Send + Sync>;
+
+//a method of a primitive value (like len of arrays), the body gets the value as self_value
+#[derive(Clone)]
+pub struct Method {
+    pub needed_arguments : Vec<String>,
+    pub body : FnType
+}
+
+//every interpreter owns a registry, the bodies of named functions are looked up in here when they
+//are called. the globals are the natives (built in and added by an embedder), they are put into the
+//global environment of the interpreter and of every module it imports.
+//
+//methods are kept apart from the functions and keyed by the type of their value and their name (for
+//example array::len), so natives and closures of the host can never shadow them
+#[derive(Clone, Default)]
+pub struct NativeRegistry {
+    pub functions : HashMap<String, FnType>,
+    pub methods : HashMap<String, Method>,
+    pub globals : HashMap<String, Value>
+}
+
+impl NativeRegistry {
+    //the key a method of the given type is registered under
+    pub fn method_key(type_name : &str, name : &str) -> String {
+        format!("{type_name}::{name}")
+    }
 
-pub type RegisteredFnMap = Mutex<Option<HashMap<String, FnType>>>;
+    pub fn register_method(&mut self, type_name : &str, name : &str, needed_arguments : Vec<&str>, body : FnType){
+        self.methods.insert(Self::method_key(type_name, name), Method {
+            needed_arguments : needed_arguments.iter().map(|x|x.to_string()).collect(),
+            body
+        });
+    }
+
+    //the method with the given name of the value, as a function bound to the value
+    pub fn method(&self, self_value : &Value, name : &str) -> Option<Value> {
+        let method = self.methods.get(&Self::method_key(self_value.type_name(), name))?;
+
+        Some(Value::primitive_method(name, method.needed_arguments.clone(), self_value.clone()))
+    }
+}
+
+impl fmt::Debug for NativeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativeRegistry")
+            .field("functions", &self.functions.keys().collect::<Vec<_>>())
+            .field("methods", &self.methods.keys().collect::<Vec<_>>())
+            .field("globals", &self.globals.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+pub type RegisteredFnMap = Arc<Mutex<NativeRegistry>>;
 
 //Functions are either built into rust (rust closures) or defined as a procedure in thor itself
 //both do the same but have different data to them
@@ -381,16 +990,16 @@ pub enum Function{
     },
     ThorFunction {
         name : String,
-        body: Vec<Statement>,
+        body: Arc<Chunk>,
         needed_arguments: Vec<String>,
+        arity : Arity,
         closure: Arc<Mutex<Environment>>,
     },
     NamedFunction{
         name : String, 
         needed_arguments : Vec<String>,
         self_value : Option<Box<Value>>, 
-        env_state : Option<EnvState>,
-        var_name : Option<String>
+        env_state : Option<EnvState>
     }
 }
 
@@ -400,10 +1009,10 @@ impl Function {
             Self::LibFunction { name, needed_arguments, self_value, mutating } => {
                 name.to_string()
             },
-            Self::ThorFunction { name, body, needed_arguments, closure } => {
+            Self::ThorFunction { name, needed_arguments, .. } => {
                 name.to_string()
             }
-            Self::NamedFunction { name, needed_arguments, self_value, env_state, var_name } => {
+            Self::NamedFunction { name, needed_arguments, self_value, env_state } => {
                 name.to_string()
             }
         }
@@ -415,16 +1024,24 @@ impl Function {
             Self::LibFunction { name, needed_arguments, self_value, mutating } => {
                 args = needed_arguments.to_vec()
             },
-            Self::ThorFunction { name, body, needed_arguments, closure } => {
+            Self::ThorFunction { name, needed_arguments, .. } => {
                 args = needed_arguments.to_vec()
             }
-            Self::NamedFunction { name, needed_arguments, self_value, env_state, var_name } => {
+            Self::NamedFunction { name, needed_arguments, self_value, env_state } => {
                 args = needed_arguments.to_vec()
             }
         }
 
         return args;
     }
+
+    //natives always take exactly their arguments
+    pub fn arity(&self) -> Arity{
+        match self{
+            Self::ThorFunction { arity, .. } => *arity,
+            _ => Arity::exactly(self.get_args().len())
+        }
+    }
 }
 
 //later i have to implement equality for functions
@@ -449,10 +1066,8 @@ impl fmt::Debug for Function {
                     .finish()
             },
             Function::ThorFunction {
-                body: _,
                 needed_arguments,
-                closure: _,
-                name,
+                ..
             } => f
                 .debug_struct("Function")
                 .field("args", needed_arguments)
@@ -466,18 +1081,74 @@ impl fmt::Debug for Function {
 //i rewrote this to improve the code readability and logic, unlike before we can just get the value
 //given that it has some type, data that is not represantable simply cant exist and we dont have no
 //unwraps all over the place anymore
+//
+//arrays and objects are shared, copying the value only copies the reference to them
 #[derive(PartialEq, Debug, Clone)]
 pub enum ValueType {
     String(String),
     Number(f64),
     Bool(bool),
     Function(Function),
-    Array(Vec<Value>),
+    Array(Shared<Vec<Value>>),
     Error(ThorLangError),
-    Object,
+    Object(Shared<HashMap<String, Value>>),
     Nil,
 }
 
+//data that lives on the heap and is shared by every value pointing to it (like arrays and objects
+//in javascript), changes made through one variable can be seen through all the others
+#[derive(Default)]
+pub struct Shared<T>(Arc<Mutex<T>>);
+
+impl<T> Shared<T> {
+    pub fn new(value : T) -> Self {
+        Shared(Arc::new(Mutex::new(value)))
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.0.lock().unwrap()
+    }
+
+    //the address of the data, values that share their data have the same id
+    pub fn id(&self) -> usize {
+        Arc::as_ptr(&self.0) as usize
+    }
+}
+
+//cloning only clones the reference
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(self.0.clone())
+    }
+}
+
+//prints the data itself and not the mutex around it, data that is already being printed (an array
+//that contains itself) is locked and shown as [...] or {...}
+impl fmt::Debug for Shared<Vec<Value>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.try_lock() {
+            Ok(data) => data.fmt(f),
+            Err(_) => write!(f, "[...]")
+        }
+    }
+}
+
+impl fmt::Debug for Shared<HashMap<String, Value>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.try_lock() {
+            Ok(data) => data.fmt(f),
+            Err(_) => write!(f, "{{...}}")
+        }
+    }
+}
+
+//two arrays or objects are only equal when they are the same one
+impl<T> PartialEq for Shared<T> {
+    fn eq(&self, other : &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
 
 
 //this is still the same, everything 
@@ -485,8 +1156,8 @@ pub enum ValueType {
 pub struct Value {
     pub value: ValueType,
     pub fields: HashMap<String, Value>,
-    pub return_true: bool,
-    pub listeners : Option<Vec<Vec<Statement>>>,
+    //functions (without arguments) that run when the variable holding this value is assigned to
+    pub listeners : Option<Vec<Value>>,
 
     pub library : Option<Arc<Library>>
 }
@@ -525,33 +1196,104 @@ impl Value {
 
     pub fn to_arr(&self) -> Option<Vec<Value>>{
         match &self.value{
-            ValueType::Array(arr) => Some(arr.to_vec()),
+            ValueType::Array(arr) => Some(arr.lock().to_vec()),
             _ => None
         }
     }
 
     pub fn to_ob(&self) -> Option<HashMap<String, Value>>{
         match &self.value{
-            ValueType::Object => Some(self.fields.clone()),
+            ValueType::Object(obj) => Some(obj.lock().clone()),
             _ => None
         }
     }
 
+    //the field of an object or a field that was attached to the value (like the functions of a
+    //library)
+    pub fn get_field(&self, key : &str) -> Option<Value>{
+        if let ValueType::Object(obj) = &self.value{
+            if let Some(field) = obj.lock().get(key){
+                return Some(field.clone());
+            }
+        }
+
+        self.fields.get(key).cloned()
+    }
+
+    //copies arrays and objects all the way down, so the copy doesn't share anything with the
+    //original
+    pub fn deep_copy(&self) -> Value{
+        self.deep_copy_with(&mut HashMap::new())
+    }
+
+    //copies holds the copy of every array and object by the id of the original, data that is found
+    //more than once (or contains itself) is copied once and the copies share it the same way
+    fn deep_copy_with(&self, copies : &mut HashMap<usize, ValueType>) -> Value{
+        let mut copy = self.clone();
+
+        match &self.value{
+            ValueType::Array(arr) => {
+                if let Some(done) = copies.get(&arr.id()) {
+                    copy.value = done.clone();
+                    return copy;
+                }
+
+                let shared = Shared::new(vec![]);
+                copies.insert(arr.id(), ValueType::Array(shared.clone()));
+
+                let elements = arr.lock().clone();
+                *shared.lock() = elements.iter().map(|element| element.deep_copy_with(copies)).collect();
+
+                copy.value = ValueType::Array(shared);
+            },
+            ValueType::Object(obj) => {
+                if let Some(done) = copies.get(&obj.id()) {
+                    copy.value = done.clone();
+                    return copy;
+                }
+
+                let shared = Shared::new(HashMap::new());
+                copies.insert(obj.id(), ValueType::Object(shared.clone()));
+
+                let fields = obj.lock().clone();
+                *shared.lock() = fields.iter().map(|(key, field)| (key.to_string(), field.deep_copy_with(copies))).collect();
+
+                copy.value = ValueType::Object(shared);
+            },
+            _ => ()
+        }
+
+        copy
+    }
+
+    //the name of the type of the value, the same one type_of returns
+    pub fn type_name(&self) -> &'static str{
+        match &self.value{
+            ValueType::String(_str) => "string",
+            ValueType::Number(_num) => "number",
+            ValueType::Nil => "nil",
+            ValueType::Object(_obj) => "object",
+            ValueType::Array(_arr) => "array",
+            ValueType::Function(_func) => "function",
+            ValueType::Bool(_bool) => "bool",
+            ValueType::Error(_err) => "error",
+        }
+    }
+
     //conversion stops here
     
     pub fn array(value: Vec<Value>) -> Self {
         Value {
-            value: ValueType::Array(value),
+            value: ValueType::Array(Shared::new(value)),
             ..Value::default()
         }
     }
 
     pub fn object(value : HashMap<&'static str, Value>) -> Self{
         Value{
-            value : ValueType::Object,
-            fields : value.iter().map(|(key, value)|{
+            value : ValueType::Object(Shared::new(value.iter().map(|(key, value)|{
                 (key.to_string(), value.clone())
-            }).collect(),
+            }).collect())),
             ..Default::default()
         }
     }
@@ -605,8 +1347,7 @@ impl Value {
                 self_value : None, 
                 needed_arguments : needed_arguments.iter().map(|x|x.to_string()).collect(),
                 name : name.to_string(), 
-                env_state : Some(env),
-                var_name : None
+                env_state : Some(env)
             }),
             ..Default::default()
         } 
@@ -618,58 +1359,45 @@ impl Value {
                 self_value : None, 
                 needed_arguments : needed_arguments.iter().map(|x|x.to_string()).collect(),
                 name : name.to_string(), 
-                env_state : None,
-                var_name : None
+                env_state : None
             }),
             ..Default::default()
         } 
     }
 
-    pub fn named_function(name : &'static str, needed_arguments : Vec<&str>, self_value: Option<Box<Value>>, var_name : Option<String>, env_state : Option<EnvState>) -> Self{
+    pub fn named_function(name : &'static str, needed_arguments : Vec<&str>, self_value: Option<Box<Value>>, env_state : Option<EnvState>) -> Self{
         Value{
             value : ValueType::Function(Function::NamedFunction{
                 self_value, 
                 needed_arguments : needed_arguments.iter().map(|x|x.to_string()).collect(),
                 name : name.to_string(), 
-                env_state,
-                var_name
+                env_state
             }),
             ..Default::default()
         } 
     }
 
-    pub fn primitive_method(name : &'static str, needed_arguments : Vec<&str>, self_value: Value) -> Self{
+    //the body of a primitive method is looked up in the methods of the registry by the type of the
+    //self value and the name
+    pub fn primitive_method(name : &str, needed_arguments : Vec<String>, self_value: Value) -> Self{
         Value{
             value : ValueType::Function(Function::NamedFunction{
                 self_value : Some(Box::new(self_value)),
                 env_state : None,
-                var_name : None,
                 name : name.to_string(), 
-                needed_arguments : needed_arguments.iter().map(|x|x.to_string()).collect()
+                needed_arguments
             }),
             ..Default::default()
         }
     }
 
+    //registers the body of a named function in the given registry, a body that is already registered
+    //under the same name is replaced (natives added by an embedder are registered last so they win
+    //over the defaults)
     pub fn register_function_body(&self, map: &RegisteredFnMap, function : FnType) -> Self{
 
-        let mut fn_map = map.lock().unwrap();
-      
-        
-
-        if let ValueType::Function(Function::NamedFunction { name, needed_arguments, self_value, env_state, var_name }) = &self.value{
-            if let Some(ref mut inner_map) = *fn_map{
-                
-                //check if the function is already registered
-                if let Some(_) = inner_map.get(name){
-                    return self.clone()
-                }
-
-                inner_map.insert(name.to_string(), function);
-            }else{
-                *fn_map = Some(HashMap::new());
-                fn_map.as_mut().unwrap().insert(name.to_string(), function);
-            }
+        if let ValueType::Function(Function::NamedFunction { name, .. }) = &self.value{
+            map.lock().unwrap().functions.insert(name.to_string(), function);
         }
         self.clone()
     }
@@ -678,12 +1406,14 @@ impl Value {
     pub fn thor_function(
         name : String,
         arguments: Vec<String>,
-        body: Vec<Statement>,
+        arity : Arity,
+        body: Arc<Chunk>,
         closure: Arc<Mutex<Environment>>,
     ) -> Self {
         Value {
             value: ValueType::Function(Function::ThorFunction {
                 needed_arguments: arguments,
+                arity,
                 body,
                 closure,
                 name
@@ -731,7 +1461,7 @@ impl Value {
             ValueType::Function(Function::LibFunction { name, needed_arguments,  self_value, mutating })=> {
                 map.insert(name.to_string(), self.clone());
             },
-            ValueType::Function(Function::NamedFunction { name, needed_arguments, self_value, env_state,  var_name}) => {
+            ValueType::Function(Function::NamedFunction { name, needed_arguments, self_value, env_state }) => {
                 map.insert(name.to_string(), self.clone());
             },
             _ => ()
@@ -746,7 +1476,6 @@ impl Default for Value {
         Value {
             value: ValueType::Nil,
             fields: HashMap::new(),
-            return_true: false,
             listeners : None,
             library : None
         }
@@ -756,6 +1485,12 @@ impl Default for Value {
 
 //helper function to pretty print values (especially array and objects, later functions as well)
 pub fn stringify_value(val: Value) -> String {
+    stringify_nested(val, &mut vec![])
+}
+
+//printing holds the ids of the arrays and objects the value is inside of, one that contains itself
+//is printed as [...] or {...} the second time
+fn stringify_nested(val: Value, printing: &mut Vec<usize>) -> String {
     let mut ret_val = "".to_string();
 
     match val.value {
@@ -763,14 +1498,22 @@ pub fn stringify_value(val: Value) -> String {
             if let ThorLangError::ThorLangException {
                 exception,
                 throw_token_index : _,
-            } = err
+            } = err.untraced().clone()
             {
-                ret_val = format!("Error({})", stringify_value(*exception));
+                ret_val = format!("Error({})", stringify_nested(*exception, printing));
             } else {
-                ret_val = format!("{:?}", err);
+                ret_val = format!("Error({})", err.kind());
             }
         }
         ValueType::Array(arr) => {
+            if printing.contains(&arr.id()) {
+                return "[...]".to_string();
+            }
+
+            printing.push(arr.id());
+
+            let arr = arr.lock().clone();
+
             ret_val += "[";
 
             //add a comma for every value folowing the first one
@@ -779,10 +1522,12 @@ pub fn stringify_value(val: Value) -> String {
                     ret_val += ", "
                 }
                 //move through the array recursively
-                ret_val += &stringify_value(arr.get(i).unwrap().clone())
+                ret_val += &stringify_nested(arr.get(i).unwrap().clone(), printing)
             }
 
-            ret_val += "]"
+            ret_val += "]";
+
+            printing.pop();
         }
         ValueType::Bool(b) => {
             ret_val = b.to_string();
@@ -796,8 +1541,14 @@ pub fn stringify_value(val: Value) -> String {
         ValueType::Nil => {
             ret_val = "nil".to_string();
         }
-        ValueType::Object => {
-            let obj = val.fields;
+        ValueType::Object(obj) => {
+            if printing.contains(&obj.id()) {
+                return "{...}".to_string();
+            }
+
+            printing.push(obj.id());
+
+            let obj = obj.lock().clone();
 
             ret_val += "{ ";
 
@@ -811,10 +1562,12 @@ pub fn stringify_value(val: Value) -> String {
                 let value = obj.values().nth(i).unwrap();
 
                 //again move through the object recursively
-                ret_val += &(key.to_string() + " : " + &stringify_value(value.clone()));
+                ret_val += &(key.to_string() + " : " + &stringify_nested(value.clone(), printing));
             }
 
-            ret_val += " }"
+            ret_val += " }";
+
+            printing.pop();
         }
         _ => {
             ret_val = "Function".to_string();
@@ -828,6 +1581,17 @@ pub fn stringify_value(val: Value) -> String {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ThorLangError{
+    //lexer errors, there are no tokens yet so they point at the line and column directly
+    InvalidEscape{
+        escape : String,
+        line : i32,
+        column : i32
+    },
+    UnterminatedString{
+        line : i32,
+        column : i32
+    },
+
     UnexpectedToken{
         expected : Vec<TokenType>,
         encountered : usize 
@@ -846,9 +1610,19 @@ pub enum ThorLangError{
     },
     FunctionArityError{
         function_paren_token : usize,
-        needed_arguments_length : usize,
+        accepted_arguments : Arity,
         arguments_length : usize
     },
+    //a named argument that is not a parameter of the function
+    UnknownArgument{
+        function_paren_token : usize,
+        name : String
+    },
+    //an argument that is passed by position and by name (or by name twice)
+    DuplicateArgument{
+        function_paren_token : usize,
+        name : String
+    },
     OperationArityError{
         operator_token_index : usize,
         expected_arguments : usize,
@@ -860,6 +1634,17 @@ pub enum ThorLangError{
     UnknownValueError{
         identifier_token_index : usize
     },
+    //found by the resolver before the code runs
+    UseBeforeDeclaration{
+        identifier_token_index : usize
+    },
+    DuplicateDeclaration{
+        name_token_index : usize
+    },
+    //break or continue outside of a loop
+    NotInLoop{
+        keyword_token_index : usize
+    },
     
     //can be used to throw userside
     ThorLangException{
@@ -869,18 +1654,137 @@ pub enum ThorLangError{
     EvalError{
         operation_token_index : usize
     },
+    //% and ~/ with 0 on the right
+    DivisionByZero{
+        operator_token_index : usize
+    },
+    //a value that doesn't fit the pattern of a destructuring let or for, expected describes what is
+    //missing (like "an array" or "an element at index 2")
+    DestructuringError{
+        token_index : usize,
+        expected : String,
+        value : Box<Value>
+    },
+    //none of the arms of a match matched the value
+    NonExhaustiveMatch{
+        match_token_index : usize,
+        value : Box<Value>
+    },
+    //a computed field name that is not a string, number or bool
+    UnhashableKey{
+        dot_token_index : usize,
+        key : Box<Value>
+    },
 
     RuntimeError{
         message : String
     },
 
-    UnknownError
+    UnknownError,
+
+    //an error that left one or more calls (or happened in code from another source), the trace
+    //holds the calls from the innermost to the outermost and source the code the token indices of
+    //the error point into
+    Traced{
+        error : Box<ThorLangError>,
+        source : Option<Arc<Source>>,
+        trace : Vec<TraceFrame>
+    }
 }
 
 
 //easier methods to return nice errors
 impl ThorLangError {
 
+    //the code shown in front of the message (error[E0003]), codes never change once they are given
+    //out, new variants get the next free number
+    pub fn code(&self) -> &'static str{
+        return match self{
+            ThorLangError::InvalidEscape{..} => "E0001",
+            ThorLangError::UnterminatedString{..} => "E0002",
+            ThorLangError::UnexpectedToken{..} => "E0003",
+            ThorLangError::IndexError{..} => "E0004",
+            ThorLangError::RetrievalError{..} => "E0005",
+            ThorLangError::FunctionArityError{..} => "E0006",
+            ThorLangError::UnknownArgument{..} => "E0007",
+            ThorLangError::DuplicateArgument{..} => "E0008",
+            ThorLangError::OperationArityError{..} => "E0009",
+            ThorLangError::UnknownFunctionError{..} => "E0010",
+            ThorLangError::UnknownValueError{..} => "E0011",
+            ThorLangError::UseBeforeDeclaration{..} => "E0012",
+            ThorLangError::DuplicateDeclaration{..} => "E0013",
+            ThorLangError::NotInLoop{..} => "E0014",
+            ThorLangError::ThorLangException{..} => "E0015",
+            ThorLangError::EvalError{..} => "E0016",
+            ThorLangError::DivisionByZero{..} => "E0017",
+            ThorLangError::DestructuringError{..} => "E0018",
+            ThorLangError::NonExhaustiveMatch{..} => "E0019",
+            ThorLangError::RuntimeError{..} => "E0020",
+            ThorLangError::UnknownError => "E0021",
+            ThorLangError::UnhashableKey{..} => "E0022",
+            ThorLangError::Traced{error, ..} => error.code(),
+        };
+    }
+
+    //the name of the kind of error, error values in thorlang expose it as their kind field
+    pub fn kind(&self) -> &'static str{
+        return match self{
+            ThorLangError::InvalidEscape{..} => "InvalidEscape",
+            ThorLangError::UnterminatedString{..} => "UnterminatedString",
+            ThorLangError::UnexpectedToken{..} => "UnexpectedToken",
+            ThorLangError::IndexError{..} => "IndexError",
+            ThorLangError::RetrievalError{..} => "RetrievalError",
+            ThorLangError::FunctionArityError{..} => "FunctionArityError",
+            ThorLangError::UnknownArgument{..} => "UnknownArgument",
+            ThorLangError::DuplicateArgument{..} => "DuplicateArgument",
+            ThorLangError::OperationArityError{..} => "OperationArityError",
+            ThorLangError::UnknownFunctionError{..} => "UnknownFunctionError",
+            ThorLangError::UnknownValueError{..} => "UnknownValueError",
+            ThorLangError::UseBeforeDeclaration{..} => "UseBeforeDeclaration",
+            ThorLangError::DuplicateDeclaration{..} => "DuplicateDeclaration",
+            ThorLangError::NotInLoop{..} => "NotInLoop",
+            ThorLangError::ThorLangException{..} => "ThorLangException",
+            ThorLangError::EvalError{..} => "EvalError",
+            ThorLangError::DivisionByZero{..} => "DivisionByZero",
+            ThorLangError::DestructuringError{..} => "DestructuringError",
+            ThorLangError::NonExhaustiveMatch{..} => "NonExhaustiveMatch",
+            ThorLangError::RuntimeError{..} => "RuntimeError",
+            ThorLangError::UnknownError => "UnknownError",
+            ThorLangError::UnhashableKey{..} => "UnhashableKey",
+            ThorLangError::Traced{error, ..} => error.kind(),
+        };
+    }
+
+    //the error without its trace
+    pub fn untraced(&self) -> &ThorLangError{
+        if let ThorLangError::Traced{error, ..} = self{
+            return error.untraced();
+        }
+
+        return self;
+    }
+
+    //adds the calls the error left on its way out (innermost first), the source is only kept the
+    //first time since that is where the error happened
+    pub fn traced(self, source : Option<Arc<Source>>, calls : Vec<TraceFrame>) -> ThorLangError{
+        return match self{
+            ThorLangError::Traced{error, source : inner_source, mut trace} => {
+                trace.extend(calls);
+
+                ThorLangError::Traced{
+                    error,
+                    source : inner_source.or(source),
+                    trace
+                }
+            }
+            error => ThorLangError::Traced{
+                error : Box::new(error),
+                source,
+                trace : calls
+            }
+        };
+    }
+
     pub fn runtime_error(message : String) -> Result<Value, ThorLangError>{
         Err(ThorLangError::RuntimeError{
             message
@@ -923,11 +1827,11 @@ impl ThorLangError {
     }
 
     //function "..." expeceted n arguments but got m
-    pub fn function_arity_error(function_paren_token : usize, needed_arguments_length : usize, arguments_length : usize) -> Result<Value, ThorLangError>{
+    pub fn function_arity_error(function_paren_token : usize, accepted_arguments : Arity, arguments_length : usize) -> Result<Value, ThorLangError>{
 
         Err(ThorLangError::FunctionArityError{
             function_paren_token,
-            needed_arguments_length, 
+            accepted_arguments, 
             arguments_length
         })
 